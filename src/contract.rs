@@ -0,0 +1,432 @@
+use crate::doc_item::*;
+
+///  Keywords that open a clause after a Verus function signature.
+///  Clauses we do not document (`opens_invariants`, `no_unwind`) are still
+///  listed so their text does not leak into the preceding clause.
+const CLAUSE_KEYWORDS: [&str; 7] = [
+    "requires",
+    "recommends",
+    "ensures",
+    "returns",
+    "decreases",
+    "opens_invariants",
+    "no_unwind",
+];
+
+///  Parse the clause lists out of a function header, i.e. the source text of a
+///  function from its start up to (not including) its body.
+pub fn parse_contract(header: &str) -> RtContract {
+    let text = strip_comments(header);
+    let mut contract = RtContract::default();
+
+    let starts = find_clause_keywords(&text);
+    for (idx, &(pos, keyword)) in starts.iter().enumerate() {
+        let body_start = pos + keyword.len();
+        let body_end = starts.get(idx + 1).map_or(text.len(), |&(next, _)| next);
+        let clauses = split_top_level_commas(&text[body_start..body_end]);
+
+        let target = match keyword {
+            "requires" => &mut contract.requires,
+            "recommends" => &mut contract.recommends,
+            "ensures" => &mut contract.ensures,
+            "returns" => &mut contract.returns,
+            "decreases" => &mut contract.decreases,
+            _ => continue,
+        };
+        target.extend(clauses);
+    }
+
+    contract
+}
+
+///  Parse the signature out of a function header: everything before the first
+///  clause keyword, with comments removed and whitespace normalized.
+pub fn parse_signature(header: &str) -> String {
+    let text = strip_comments(header);
+    let end = find_clause_keywords(&text)
+        .first()
+        .map_or(text.len(), |&(pos, _)| pos);
+
+    normalize_code(&text[..end])
+}
+
+///  Collapse whitespace in a code fragment and drop the spaces and trailing
+///  commas that multi-line formatting leaves inside brackets.
+pub fn normalize_code(text: &str) -> String {
+    let text = collapse_whitespace(text)
+        .replace("( ", "(")
+        .replace("[ ", "[")
+        .replace(" )", ")")
+        .replace(" ]", "]")
+        .replace(" ,", ",");
+    drop_trailing_commas(&tighten_generics(&text)).trim_end_matches(',').to_string()
+}
+
+///  Remove the spaces just inside generic argument lists, `Vec< T >` becoming
+///  `Vec<T>`. A `<` opens one only right after a path segment, so comparisons
+///  such as `a < b` and `x >= 0` keep their spacing.
+fn tighten_generics(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = String::with_capacity(text.len());
+    let mut depth = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'"' => {
+                let end = skip_string(bytes, i);
+                out.push_str(&text[i..end]);
+                i = end;
+                continue;
+            }
+            b'<' if out.ends_with(|c: char| c.is_alphanumeric() || c == '_' || c == ':') => {
+                depth += 1;
+                out.push('<');
+                i += 1;
+                while bytes.get(i) == Some(&b' ') {
+                    i += 1;
+                }
+                continue;
+            }
+            //  `->` and `=>` inside the arguments don't close them
+            b'>' if depth > 0 && !out.ends_with(['-', '=']) => {
+                depth -= 1;
+                out.truncate(out.trim_end().len());
+            }
+            _ => {}
+        }
+        let ch = text[i..].chars().next().unwrap_or(' ');
+        out.push(ch);
+        i += ch.len_utf8();
+    }
+
+    out
+}
+
+///  Remove the comma before a closing bracket, except in a parenthesised
+///  list of one element: `(T,)` is a 1-tuple, not `(T)`.
+fn drop_trailing_commas(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = String::with_capacity(text.len());
+    //  Whether each open `(` has seen a comma so far
+    let mut parens: Vec<bool> = Vec::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'"' => {
+                let end = skip_string(bytes, i);
+                out.push_str(&text[i..end]);
+                i = end;
+                continue;
+            }
+            b'(' => parens.push(false),
+            b')' => {
+                parens.pop();
+            }
+            b',' => {
+                let one_tuple = bytes.get(i + 1) == Some(&b')') && parens.last() == Some(&false);
+                if matches!(bytes.get(i + 1), Some(b')' | b']' | b'>')) && !one_tuple {
+                    i += 1;
+                    continue;
+                }
+                if let Some(seen) = parens.last_mut() {
+                    *seen = true;
+                }
+            }
+            _ => {}
+        }
+        let ch = text[i..].chars().next().unwrap_or(' ');
+        out.push(ch);
+        i += ch.len_utf8();
+    }
+
+    out
+}
+
+///  Find byte offsets of clause keywords that appear outside any brackets.
+fn find_clause_keywords(text: &str) -> Vec<(usize, &'static str)> {
+    let bytes = text.as_bytes();
+    let mut found = Vec::new();
+    let mut depth: i32 = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'(' | b'[' | b'{' => depth += 1,
+            b')' | b']' | b'}' => depth -= 1,
+            b'"' => {
+                i = skip_string(bytes, i);
+                continue;
+            }
+            b'\'' => {
+                if let Some(end) = skip_char(text, i) {
+                    i = end;
+                    continue;
+                }
+            }
+            _ if depth == 0 && (i == 0 || !is_ident_byte(bytes[i - 1])) => {
+                if let Some(keyword) = CLAUSE_KEYWORDS.iter().find(|k| {
+                    bytes[i..].starts_with(k.as_bytes())
+                        && !bytes.get(i + k.len()).is_some_and(|b| is_ident_byte(*b))
+                }) {
+                    found.push((i, *keyword));
+                    i += keyword.len();
+                    continue;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+
+    found
+}
+
+///  Split a clause list at commas that are not nested inside brackets, a
+///  turbofish, or the `|..|` binder of a quantifier or closure, collapsing
+///  whitespace in each resulting clause.
+pub fn split_top_level_commas(text: &str) -> Vec<String> {
+    let bytes = text.as_bytes();
+    let mut parts = Vec::new();
+    let mut depth: i32 = 0;
+    let mut angle: i32 = 0;
+    let mut start = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'(' | b'[' | b'{' => depth += 1,
+            b')' | b']' | b'}' => depth -= 1,
+            //  Inside a turbofish every `<` nests, e.g. `Map::<Seq<u8>, int>`
+            b'<' if angle > 0 || text[..i].ends_with("::") => angle += 1,
+            b'|' if opens_binder(&text[..i]) => {
+                //  Skip the parameters up to the closing `|`; `||` has none
+                i = match text[i + 1..].find('|') {
+                    Some(close) => i + 1 + close + 1,
+                    None => bytes.len(),
+                };
+                continue;
+            }
+            b'>' if angle > 0 && !text[..i].ends_with('=') && !text[..i].ends_with('-') => {
+                angle -= 1
+            }
+            b'"' => {
+                i = skip_string(bytes, i);
+                continue;
+            }
+            b'\'' => {
+                if let Some(end) = skip_char(text, i) {
+                    i = end;
+                    continue;
+                }
+            }
+            b',' if depth == 0 && angle == 0 => {
+                parts.push(&text[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    parts.push(&text[start..]);
+
+    parts
+        .into_iter()
+        .map(collapse_whitespace)
+        .filter(|p| !p.is_empty())
+        .collect()
+}
+
+///  Whether a `|` preceded by `before` opens a binder: the parameters of
+///  `forall`, `exists` or `choose`, or of a closure, which starts an
+///  expression. Otherwise it is an operator such as `||`.
+fn opens_binder(before: &str) -> bool {
+    let before = before.trim_end();
+    let after_keyword = ["forall", "exists", "choose", "move"].iter().any(|k| {
+        before.strip_suffix(k).is_some_and(|rest| !rest.bytes().last().is_some_and(is_ident_byte))
+    });
+    after_keyword || before.is_empty() || before.ends_with(['(', '[', '{', ',', '='])
+}
+
+///  Remove `//` and `/* */` comments, leaving string and char literals intact.
+pub fn strip_comments(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = String::with_capacity(text.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let literal_end = match bytes[i] {
+            b'"' => Some(skip_string(bytes, i)),
+            b'\'' => skip_char(text, i),
+            _ => None,
+        };
+        if let Some(end) = literal_end {
+            out.push_str(&text[i..end]);
+            i = end;
+        } else if text[i..].starts_with("//") {
+            i = text[i..].find('\n').map_or(bytes.len(), |n| i + n);
+        } else if text[i..].starts_with("/*") {
+            i = text[i + 2..].find("*/").map_or(bytes.len(), |n| i + 2 + n + 2);
+            out.push(' ');
+        } else {
+            let ch = text[i..].chars().next().unwrap_or(' ');
+            out.push(ch);
+            i += ch.len_utf8();
+        }
+    }
+
+    out
+}
+
+///  Return the byte offset just past the string literal starting at `start`.
+fn skip_string(bytes: &[u8], start: usize) -> usize {
+    let mut i = start + 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'"' => return i + 1,
+            _ => i += 1,
+        }
+    }
+    bytes.len()
+}
+
+///  Return the byte offset just past the char literal starting at `start`,
+///  e.g. `'"'` or `'\''`, or None if the `'` begins a lifetime such as `'a`.
+fn skip_char(text: &str, start: usize) -> Option<usize> {
+    let rest = &text[start + 1..];
+    if let Some(escaped) = rest.strip_prefix('\\') {
+        //  The escaped character may itself be `'`; `\u{..}` runs to the next `'`
+        let first = escaped.chars().next()?;
+        let close = escaped[first.len_utf8()..].find('\'')?;
+        return Some(start + 2 + first.len_utf8() + close + 1);
+    }
+    let ch = rest.chars().next()?;
+    rest[ch.len_utf8()..].starts_with('\'').then_some(start + 1 + ch.len_utf8() + 1)
+}
+
+fn is_ident_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_'
+}
+
+///  Collapse runs of whitespace into single spaces and trim the ends.
+pub fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clauses_are_collected_by_keyword() {
+        let contract = parse_contract(
+            "fn f(x: u8, y: u8) -> (r: u8) requires x > 0, y > 0, recommends x < y ensures r == x + y, returns x + y decreases x, y",
+        );
+        assert_eq!(contract.requires, vec!["x > 0", "y > 0"]);
+        assert_eq!(contract.recommends, vec!["x < y"]);
+        assert_eq!(contract.ensures, vec!["r == x + y"]);
+        assert_eq!(contract.returns, vec!["x + y"]);
+        assert_eq!(contract.decreases, vec!["x", "y"]);
+    }
+
+    #[test]
+    fn keywords_in_brackets_strings_and_comments_are_ignored() {
+        let contract = parse_contract(
+            "fn f(s: Seq<u8>) requires ensures_ok(s), s@ != \"ensures\"@, // ensures x\n /* requires y */ ensures s.len() > 0",
+        );
+        assert_eq!(contract.requires, vec!["ensures_ok(s)", "s@ != \"ensures\"@"]);
+        assert_eq!(contract.ensures, vec!["s.len() > 0"]);
+    }
+
+    #[test]
+    fn undocumented_clauses_end_the_previous_one() {
+        let contract = parse_contract("fn f() ensures true opens_invariants any no_unwind");
+        assert_eq!(contract.ensures, vec!["true"]);
+    }
+
+    #[test]
+    fn strings_with_commas_are_not_split() {
+        let clauses = split_top_level_commas("s@ == \"a, b\"@, t@ == \"\\\", \"@");
+        assert_eq!(clauses, vec!["s@ == \"a, b\"@", "t@ == \"\\\", \"@"]);
+    }
+
+    #[test]
+    fn brackets_and_turbofish_are_not_split() {
+        let clauses = split_top_level_commas("f(a, b) == [1, 2], m == Map::<u8, int>::empty(), x >= 0");
+        assert_eq!(clauses, vec!["f(a, b) == [1, 2]", "m == Map::<u8, int>::empty()", "x >= 0"]);
+    }
+
+    #[test]
+    fn quantifier_binders_are_not_split() {
+        let clauses = split_top_level_commas(
+            "forall|i: int, j: int| 0 <= i < j < s.len() ==> s[i] <= s[j], s.len() > 0",
+        );
+        assert_eq!(clauses, vec![
+            "forall|i: int, j: int| 0 <= i < j < s.len() ==> s[i] <= s[j]",
+            "s.len() > 0",
+        ]);
+
+        let clauses = split_top_level_commas("exists|a: int, b: int| a + b == n, choose|x: int, y: int| x == y");
+        assert_eq!(clauses, vec!["exists|a: int, b: int| a + b == n", "choose|x: int, y: int| x == y"]);
+    }
+
+    #[test]
+    fn closures_and_logical_or() {
+        let clauses = split_top_level_commas("r == s.map(|i, x| x + i), a || b, f(|| 1, 2)");
+        assert_eq!(clauses, vec!["r == s.map(|i, x| x + i)", "a || b", "f(|| 1, 2)"]);
+    }
+
+    #[test]
+    fn nested_generics_are_not_split() {
+        let clauses = split_top_level_commas("m == Map::<Seq<u8>, int>::empty(), v == Seq::<Seq<int>>::empty(), x > 0");
+        assert_eq!(clauses, vec!["m == Map::<Seq<u8>, int>::empty()", "v == Seq::<Seq<int>>::empty()", "x > 0"]);
+    }
+
+    #[test]
+    fn contract_with_quantifier() {
+        let contract = parse_contract(
+            "fn f(s: Seq<int>) -> (r: bool) requires s.len() > 0, ensures forall|i: int, j: int| 0 <= i < j < s.len() ==> s[i] <= s[j], r",
+        );
+        assert_eq!(contract.requires, vec!["s.len() > 0"]);
+        assert_eq!(contract.ensures, vec!["forall|i: int, j: int| 0 <= i < j < s.len() ==> s[i] <= s[j]", "r"]);
+    }
+
+    #[test]
+    fn char_literals_are_skipped() {
+        let clauses = split_top_level_commas("c == '\"', d == '(', e == ',', f == '\\'', g == '|'");
+        assert_eq!(clauses, vec!["c == '\"'", "d == '('", "e == ','", "f == '\\''", "g == '|'"]);
+
+        let contract = parse_contract("fn f(c: char) requires c != '(', c != ')' ensures c != '\"' // ensures x\n");
+        assert_eq!(contract.requires, vec!["c != '('", "c != ')'"]);
+        assert_eq!(contract.ensures, vec!["c != '\"'"]);
+    }
+
+    #[test]
+    fn lifetimes_are_not_char_literals() {
+        let clauses = split_top_level_commas("r == f::<'a, T>(x), s == g::<'b>(y), t == 'x'");
+        assert_eq!(clauses, vec!["r == f::<'a, T>(x)", "s == g::<'b>(y)", "t == 'x'"]);
+    }
+
+    #[test]
+    fn trailing_commas_are_dropped_except_in_one_tuples() {
+        assert_eq!(normalize_code("fn f(\n    a: u8,\n    b: (u8, u16,),\n) -> (T,)"), "fn f(a: u8, b: (u8, u16)) -> (T,)");
+        assert_eq!(normalize_code("let x = (y,)"), "let x = (y,)");
+        assert_eq!(normalize_code("f(g(a,), [1, 2,], Map<K, V,>)"), "f(g(a,), [1, 2], Map<K, V>)");
+    }
+
+    #[test]
+    fn comparisons_keep_their_spacing() {
+        assert_eq!(normalize_code("a < b"), "a < b");
+        assert_eq!(normalize_code("a >= b && x > 0"), "a >= b && x > 0");
+        assert_eq!(normalize_code("s.len() < N"), "s.len() < N");
+    }
+
+    #[test]
+    fn generic_arguments_are_tightened() {
+        assert_eq!(normalize_code("Vec< T >"), "Vec<T>");
+        assert_eq!(normalize_code("fn f< T: Clone >(m: Map< K, Vec< V >, >) -> Seq<u8>"), "fn f<T: Clone>(m: Map<K, Vec<V>>) -> Seq<u8>");
+        assert_eq!(normalize_code("x == Seq::< int >::empty() && a < b"), "x == Seq::<int>::empty() && a < b");
+    }
+}
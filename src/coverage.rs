@@ -0,0 +1,180 @@
+use crate::doc_item::*;
+
+use std::collections::{BTreeMap, HashSet};
+
+///  Documented items out of the items counted.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CoverageCount {
+    pub documented: usize,
+    pub total: usize,
+}
+
+impl CoverageCount {
+    fn add(&mut self, documented: bool) {
+        self.total += 1;
+        if documented {
+            self.documented += 1;
+        }
+    }
+
+    ///  Percentage of documented items; 100 when there is nothing to document.
+    pub fn percent(&self) -> f64 {
+        if self.total == 0 {
+            100.0
+        } else {
+            self.documented as f64 * 100.0 / self.total as f64
+        }
+    }
+}
+
+///  An item without a doc comment.
+#[derive(Debug, Clone)]
+pub struct UndocumentedItem {
+    pub path: String,
+    pub title: String,
+    pub file_path: String,
+    pub line_number: usize,
+}
+
+///  Doc coverage of the items in a documentation output.
+#[derive(Debug, Clone, Default)]
+pub struct CoverageReport {
+    pub total: CoverageCount,
+    ///  Per module path, in module order.
+    pub modules: Vec<(String, CoverageCount)>,
+    ///  Per function kind (`spec fn`, `proof fn`, `exec fn`) and other item kind.
+    pub kinds: Vec<(String, CoverageCount)>,
+    pub undocumented: Vec<UndocumentedItem>,
+}
+
+///  Compute doc coverage over the items in `output`, which should already be
+///  filtered to the visibility being checked. Trait members count; methods of
+///  `impl Trait for Type` blocks don't, since the trait documents them. An
+///  item re-exported at several paths counts once, at the first one.
+pub fn compute(output: &RtDocOutput) -> CoverageReport {
+    let mut report = CoverageReport::default();
+    let mut counted: HashSet<String> = HashSet::new();
+    //  Keyed by section order so function kinds come out spec, proof, exec
+    let mut kinds: BTreeMap<(u8, u8, String), CoverageCount> = BTreeMap::new();
+
+    for module in &output.modules {
+        let mut count = CoverageCount::default();
+        let mut items: Vec<&RtDocItem> = Vec::new();
+        for item in &module.items {
+            items.push(item);
+            items.extend(&item.members);
+        }
+
+        for item in items {
+            let is_trait_impl = item.impl_block.as_ref().is_some_and(|b| b.trait_path.is_some());
+            if is_trait_impl {
+                continue;
+            }
+            let path = format!("{}::{}", item.module_path, item.name);
+            if !counted.insert(item.reexport_of.clone().unwrap_or_else(|| path.clone())) {
+                continue;
+            }
+
+            let documented = item.doc_comment.is_some();
+            count.add(documented);
+            report.total.add(documented);
+            kind_entry(&mut kinds, item).add(documented);
+
+            if !documented {
+                report.undocumented.push(UndocumentedItem {
+                    path,
+                    title: item.title(),
+                    file_path: item.file_path.clone(),
+                    line_number: item.line_number,
+                });
+            }
+        }
+
+        report.modules.push((module.path.clone(), count));
+    }
+
+    report.kinds = kinds.into_iter().map(|((_, _, label), count)| (label, count)).collect();
+    report
+}
+
+///  The per-kind count an item contributes to, created on first use.
+fn kind_entry<'a>(kinds: &'a mut BTreeMap<(u8, u8, String), CoverageCount>, item: &RtDocItem) -> &'a mut CoverageCount {
+    let key = match item.item_kind {
+        RtItemKind::Function => (item.item_kind.ord(), item.kind.ord(), format!("{} fn", item.kind.as_str())),
+        //  Both are `type`; keep them apart
+        RtItemKind::TypeAlias => (RtItemKind::TypeAlias.ord(), 3, "type alias".to_string()),
+        RtItemKind::AssocType => (RtItemKind::AssocType.ord(), 3, "associated type".to_string()),
+        kind => (kind.ord(), 3, kind.as_str().to_string()),
+    };
+    kinds.entry(key).or_default()
+}
+
+///  Format the report as Markdown tables followed by the undocumented items.
+pub fn format_report(report: &CoverageReport) -> String {
+    let mut md = String::new();
+
+    md.push_str(&format!(
+        "Doc coverage: {}/{} items ({:.1}%)\n\n",
+        report.total.documented,
+        report.total.total,
+        report.total.percent(),
+    ));
+
+    format_table(&mut md, "Module", &report.modules);
+    format_table(&mut md, "Kind", &report.kinds);
+
+    if !report.undocumented.is_empty() {
+        md.push_str("Undocumented items:\n\n");
+        for item in &report.undocumented {
+            md.push_str(&format!(
+                "- `{}` ({}) — {}:{}\n",
+                item.path, item.title, item.file_path, item.line_number,
+            ));
+        }
+        md.push('\n');
+    }
+
+    md
+}
+
+///  Format one coverage table with a row per entry.
+fn format_table(md: &mut String, heading: &str, rows: &[(String, CoverageCount)]) {
+    md.push_str(&format!("| {} | Documented | Total | Coverage |\n|---|---:|---:|---:|\n", heading));
+    for (name, count) in rows {
+        md.push_str(&format!(
+            "| `{}` | {} | {} | {:.1}% |\n",
+            name,
+            count.documented,
+            count.total,
+            count.percent(),
+        ));
+    }
+    md.push('\n');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reexported_items_count_once() {
+        let f = RtDocItem { name: "f".to_string(), module_path: "crate::a".to_string(), ..Default::default() };
+        let copy = |module: &str| RtDocItem {
+            module_path: module.to_string(),
+            reexport_of: Some("crate::a::f".to_string()),
+            ..f.clone()
+        };
+        let output = RtDocOutput {
+            modules: vec![
+                RtDocModule { path: "crate".to_string(), doc: None, items: vec![copy("crate")] },
+                RtDocModule { path: "crate::a".to_string(), doc: None, items: vec![f.clone()] },
+                RtDocModule { path: "crate::b".to_string(), doc: None, items: vec![copy("crate::b")] },
+            ],
+        };
+
+        let report = compute(&output);
+        assert_eq!(report.total.total, 1);
+        let paths: Vec<&str> = report.undocumented.iter().map(|u| u.path.as_str()).collect();
+        assert_eq!(paths, vec!["crate::f"]);
+    }
+}
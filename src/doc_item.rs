@@ -10,11 +10,14 @@ pub enum FnKind {
     Exec,
 }
 
-///  Visibility of a declaration.
+///  Visibility of a declaration. `PublicIn` abstracts over the path of
+///  `pub(in path)`; `pub(self)` is modeled as `Private`.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Visibility {
     Public,
     PublicCrate,
+    PublicSuper,
+    PublicIn,
     Private,
 }
 
@@ -64,11 +67,27 @@ pub open spec fn items_grouped_by_kind(items: Seq<DocItem>) -> bool {
             kind_ord(items[i].kind) <= kind_ord(items[j].kind)
 }
 
-///  All items have Public visibility.
+///  Returns the Visibility ordering value: Private < PublicSuper/PublicIn < PublicCrate < Public.
+pub open spec fn vis_rank(v: Visibility) -> nat {
+    match v {
+        Visibility::Private => 0,
+        Visibility::PublicSuper => 1,
+        Visibility::PublicIn => 1,
+        Visibility::PublicCrate => 2,
+        Visibility::Public => 3,
+    }
+}
+
+///  All items have Public visibility; restricted `pub(...)` variants do not count.
 pub open spec fn all_items_public(items: Seq<DocItem>) -> bool {
     forall|i: int| 0 <= i < items.len() ==> items[i].visibility == Visibility::Public
 }
 
+///  All items are at least as visible as `min`.
+pub open spec fn all_items_at_least(items: Seq<DocItem>, min: Visibility) -> bool {
+    forall|i: int| 0 <= i < items.len() ==> vis_rank(items[i].visibility) >= vis_rank(min)
+}
+
 ///  Modules are sorted alphabetically by path.
 pub open spec fn modules_sorted(modules: Seq<DocModule>) -> bool {
     forall|i: int, j: int|
@@ -102,41 +121,266 @@ pub open spec fn is_permutation(a: Seq<DocItem>, b: Seq<DocItem>) -> bool {
 } //  verus!
 
 ///  Runtime representation of FnKind (mirrors the ghost enum).
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum RtFnKind {
     Spec,
     Proof,
+    #[default]
     Exec,
 }
 
-///  Runtime representation of Visibility.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///  Runtime representation of Visibility. `PublicIn` keeps the path as written;
+///  `pub(self)` is `Private`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub enum RtVisibility {
     Public,
     PublicCrate,
+    PublicSuper,
+    PublicIn(String),
+    #[default]
     Private,
 }
 
+///  Category of a documented item. Functions are further split by `RtFnKind`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RtItemKind {
+    #[default]
+    Function,
+    Struct,
+    Enum,
+    Trait,
+    ///  An associated type declared in a trait or defined in a trait impl.
+    AssocType,
+    ///  A `type` alias outside traits and impls.
+    TypeAlias,
+    ///  An `impl Trait for Type` block; consumed by `build_doc_output`.
+    Impl,
+    ///  A `mod` declaration; `has_body` is set for inline modules.
+    Module,
+    ///  One imported name of a `pub use` declaration; consumed by the visibility pass.
+    Use,
+    ///  A `broadcast group`; its members are listed in `group_members`.
+    BroadcastGroup,
+    ///  A `const`, including `spec const` and `exec const`; the mode is in `kind`.
+    Const,
+    Static,
+}
+
 ///  Runtime documentation item extracted from source.
-#[derive(Debug, Clone)]
+///  Fields that only apply to some item kinds are left empty otherwise.
+#[derive(Debug, Clone, Default)]
 pub struct RtDocItem {
     pub name: String,
     pub kind: RtFnKind,
     pub visibility: RtVisibility,
     pub is_open: bool,
+    ///  Set for `broadcast proof fn` lemmas.
+    pub is_broadcast: bool,
     pub line_number: usize,
+    pub end_line_number: usize,
     pub file_path: String,
     pub doc_comment: Option<String>,
     pub module_path: String,
+    pub signature: String,
+    ///  For constants, the value expression when it is part of the spec
+    ///  (`spec` or `open` consts).
+    pub value: Option<String>,
+    ///  For `open spec fn`s, the body with its braces removed and common
+    ///  indentation stripped.
+    pub spec_body: Option<String>,
+    ///  For structs and enums, the `View`/`DeepView` models of the type;
+    ///  set by `build_doc_output`.
+    pub models: Vec<RtAbstractModel>,
+    ///  For methods of inherent impls, set when the method states an
+    ///  invariant of its type.
+    pub invariant_kind: Option<RtInvariantKind>,
+    ///  For structs and enums, the invariants stated in their inherent impls;
+    ///  set by `extraction::attach_invariants`.
+    pub invariants: Vec<RtInvariant>,
+    ///  For type aliases and associated types defined in impls, the type after `=`.
+    pub aliased_type: Option<String>,
+    ///  Types of the crate named in `aliased_type`.
+    pub type_refs: Vec<RtXref>,
+    pub contract: RtContract,
+    pub item_kind: RtItemKind,
+    pub derives: Vec<String>,
+    pub fields: Vec<RtField>,
+    pub variants: Vec<RtVariant>,
+    ///  False for bodiless declarations such as required trait methods.
+    pub has_body: bool,
+    ///  Associated types and methods of a trait.
+    pub members: Vec<RtDocItem>,
+    ///  Known `impl` blocks of a trait found in the crate.
+    pub impls: Vec<RtDocItem>,
+    ///  The `impl` block a method is declared in (or describes, for `Impl` items).
+    pub impl_block: Option<RtImplBlock>,
+    ///  Outer attributes in source order, e.g. `#[verifier::external_body]`.
+    pub attributes: Vec<String>,
+    ///  For `Use` items, the imported path as written; for re-exported
+    ///  copies, the full path of the original definition.
+    pub reexport_of: Option<String>,
+    ///  Id of the item on its module page, e.g. `fn.name`; set by `build_doc_output`.
+    pub anchor: String,
+    ///  Spec functions of the crate called in this item's contract.
+    pub contract_refs: Vec<RtXref>,
+    ///  For spec functions, the items whose contracts call this one.
+    pub used_in: Vec<RtXref>,
+    ///  Functions called in the body, as written; `self.f(..)` is recorded as `Self::f`.
+    pub body_calls: Vec<String>,
+    ///  Attributes and body constructs whose correctness is assumed, not verified.
+    pub trust: Vec<RtTrustSite>,
+    ///  For broadcast groups, the lemmas and groups listed, in order. `path` is
+    ///  as written until resolved; `url` stays empty for members outside the crate.
+    pub group_members: Vec<RtXref>,
+    ///  For broadcast lemmas and groups, the groups that list this one.
+    pub in_groups: Vec<RtXref>,
+}
+
+///  A construct Verus takes on trust rather than verifying.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RtTrustKind {
+    ///  `#[verifier::external_body]`: the signature and contract are trusted.
+    ExternalBody,
+    ///  `#[verifier::external]`: the item is not verified at all.
+    External,
+    ///  `#[verifier::external_fn_specification]` and similar.
+    ExternalSpec,
+    ///  `#[verifier::trusted]` and similar.
+    Trusted,
+    ///  `assume_specification[...]`: a trusted contract for an external function.
+    AssumeSpecification,
+    ///  `assume(...)` in a body.
+    Assume,
+    ///  `admit()` in a body.
+    Admit,
+}
+
+///  One trusted site: an attribute on an item or an expression in its body.
+#[derive(Debug, Clone)]
+pub struct RtTrustSite {
+    pub kind: RtTrustKind,
+    pub line_number: usize,
+    ///  The attribute or expression, whitespace collapsed.
+    pub text: String,
+}
+
+///  A resolved reference to another documented item.
+#[derive(Debug, Clone, Default)]
+pub struct RtXref {
+    ///  Identifier as it appears at the reference.
+    pub name: String,
+    ///  Path of the target without the leading `crate::`, e.g. `grouping::seq_char_le`.
+    pub path: String,
+    ///  Link to the target in the output format being rendered.
+    pub url: String,
+}
+
+///  Header of an `impl` block: `impl<generics> trait_path for self_ty`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RtImplBlock {
+    pub self_ty: String,
+    pub trait_path: Option<String>,
+    pub generics: Option<String>,
+    ///  The module the impl is written in, which its paths are relative to.
+    pub module_path: String,
+    ///  Full path of the crate struct or enum `self_ty` names, if any; set by
+    ///  `scope::resolve_impls`.
+    pub self_target: Option<String>,
+    ///  Full path of the crate trait `trait_path` names, if any; set by
+    ///  `scope::resolve_impls`.
+    pub trait_target: Option<String>,
+}
+
+///  How a method states an invariant of its type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RtInvariantKind {
+    ///  A `#[verifier::type_invariant]` function, which Verus enforces.
+    TypeInvariant,
+    ///  A conventional `spec fn wf(&self) -> bool` or `inv` predicate, which
+    ///  holds wherever callers require it.
+    WellFormed,
+}
+
+///  An invariant of a type, from one of its inherent impl methods.
+#[derive(Debug, Clone)]
+pub struct RtInvariant {
+    pub kind: RtInvariantKind,
+    pub name: String,
+    pub signature: String,
+    ///  The predicate's body, for type invariants and open spec functions.
+    pub body: Option<String>,
+    pub doc_comment: Option<String>,
+    pub file_path: String,
+    pub line_number: usize,
+}
+
+///  The spec model of an exec type, from an `impl View for T` or
+///  `impl DeepView for T` block: what `self@` (or `self.deep_view()`) is.
+#[derive(Debug, Clone, Default)]
+pub struct RtAbstractModel {
+    ///  `View` or `DeepView`.
+    pub trait_name: String,
+    ///  The impl header, e.g. `impl<T> View for Vec<T>`.
+    pub header: String,
+    ///  The associated type `V`.
+    pub model_type: Option<String>,
+    ///  Types of the crate named in `model_type`.
+    pub type_refs: Vec<RtXref>,
+    ///  Body of the `open spec fn view`/`deep_view`; None when closed.
+    pub view_body: Option<String>,
+    ///  Location of the first member of the impl.
+    pub file_path: String,
+    pub line_number: usize,
+}
+
+///  A named or positional field of a struct.
+#[derive(Debug, Clone)]
+pub struct RtField {
+    pub name: String,
+    pub ty: String,
+    pub visibility: RtVisibility,
+    pub line_number: usize,
+    pub doc_comment: Option<String>,
+}
+
+///  A variant of an enum; `signature` is its normalized source text, e.g. `Some(T)`.
+#[derive(Debug, Clone)]
+pub struct RtVariant {
+    pub name: String,
+    pub signature: String,
+    pub line_number: usize,
+    pub doc_comment: Option<String>,
+}
+
+///  Runtime function contract: the clause lists following a signature.
+///  Each entry is one comma-separated clause with whitespace collapsed.
+#[derive(Debug, Clone, Default)]
+pub struct RtContract {
+    pub requires: Vec<String>,
+    pub recommends: Vec<String>,
+    pub ensures: Vec<String>,
+    pub returns: Vec<String>,
+    pub decreases: Vec<String>,
 }
 
 ///  Runtime documentation module.
 #[derive(Debug, Clone)]
 pub struct RtDocModule {
     pub path: String,
+    ///  Inner doc of the module, merged after the doc on its `mod` declaration.
+    pub doc: Option<String>,
     pub items: Vec<RtDocItem>,
 }
 
+///  A titled group of items within a module, as rendered in the output.
+#[derive(Debug, Clone)]
+pub struct RtSection<'a> {
+    pub title: String,
+    ///  Set for trait impl sections, whose title is the impl header.
+    pub impl_block: Option<&'a RtImplBlock>,
+    pub items: Vec<&'a RtDocItem>,
+}
+
 ///  Runtime documentation output.
 #[derive(Debug, Clone)]
 pub struct RtDocOutput {
@@ -161,12 +405,240 @@ impl RtFnKind {
     }
 }
 
-impl RtVisibility {
+impl RtItemKind {
+    ///  Section ordering within a module: modules, types, traits, constants,
+    ///  then functions.
+    pub fn ord(&self) -> u8 {
+        match self {
+            RtItemKind::Module => 0,
+            RtItemKind::Struct | RtItemKind::Enum | RtItemKind::TypeAlias => 1,
+            RtItemKind::Trait => 2,
+            RtItemKind::Const | RtItemKind::Static => 3,
+            RtItemKind::Function
+            | RtItemKind::AssocType
+            | RtItemKind::Impl
+            | RtItemKind::Use
+            | RtItemKind::BroadcastGroup => 4,
+        }
+    }
+
     pub fn as_str(&self) -> &'static str {
         match self {
-            RtVisibility::Public => "pub",
-            RtVisibility::PublicCrate => "pub(crate)",
-            RtVisibility::Private => "",
+            RtItemKind::Function => "fn",
+            RtItemKind::Struct => "struct",
+            RtItemKind::Enum => "enum",
+            RtItemKind::Trait => "trait",
+            RtItemKind::AssocType | RtItemKind::TypeAlias => "type",
+            RtItemKind::Impl => "impl",
+            RtItemKind::Module => "mod",
+            RtItemKind::Use => "use",
+            RtItemKind::BroadcastGroup => "broadcast group",
+            RtItemKind::Const => "const",
+            RtItemKind::Static => "static",
+        }
+    }
+
+    pub fn is_type(&self) -> bool {
+        matches!(self, RtItemKind::Struct | RtItemKind::Enum)
+    }
+}
+
+impl RtInvariantKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RtInvariantKind::TypeInvariant => "type invariant",
+            RtInvariantKind::WellFormed => "well-formedness predicate",
+        }
+    }
+}
+
+impl RtTrustKind {
+    pub const ALL: [RtTrustKind; 7] = [
+        RtTrustKind::ExternalBody,
+        RtTrustKind::External,
+        RtTrustKind::ExternalSpec,
+        RtTrustKind::Trusted,
+        RtTrustKind::AssumeSpecification,
+        RtTrustKind::Assume,
+        RtTrustKind::Admit,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RtTrustKind::ExternalBody => "external_body",
+            RtTrustKind::External => "external",
+            RtTrustKind::ExternalSpec => "external_spec",
+            RtTrustKind::Trusted => "trusted",
+            RtTrustKind::AssumeSpecification => "assume_specification",
+            RtTrustKind::Assume => "assume",
+            RtTrustKind::Admit => "admit",
+        }
+    }
+}
+
+impl RtDocOutput {
+    ///  The crate root's doc, used as the introduction of the whole document.
+    pub fn intro(&self) -> Option<&str> {
+        self.modules.iter().find(|m| m.path == "crate")?.doc.as_deref()
+    }
+}
+
+impl RtDocModule {
+    ///  Group items into non-empty sections in rendering order: types, type
+    ///  aliases, traits, constants, spec and proof functions, broadcast groups,
+    ///  exec functions, then one section per trait impl block in order of
+    ///  first appearance.
+    pub fn sections(&self) -> Vec<RtSection<'_>> {
+        let is_fn = |i: &RtDocItem, k: RtFnKind| {
+            i.item_kind == RtItemKind::Function && i.kind == k && i.impl_trait().is_none()
+        };
+        let select = |pred: &dyn Fn(&RtDocItem) -> bool| -> Vec<&RtDocItem> {
+            self.items.iter().filter(|i| pred(i)).collect()
+        };
+
+        let mut sections = vec![
+            ("Types", select(&|i| i.item_kind.is_type())),
+            ("Type Aliases", select(&|i| i.item_kind == RtItemKind::TypeAlias)),
+            ("Traits", select(&|i| i.item_kind == RtItemKind::Trait)),
+            ("Constants", select(&|i| matches!(i.item_kind, RtItemKind::Const | RtItemKind::Static))),
+            ("Spec Functions", select(&|i| is_fn(i, RtFnKind::Spec))),
+            ("Proof Functions", select(&|i| is_fn(i, RtFnKind::Proof))),
+            ("Broadcast Groups", select(&|i| i.item_kind == RtItemKind::BroadcastGroup)),
+            ("Exec Functions", select(&|i| is_fn(i, RtFnKind::Exec))),
+        ]
+        .into_iter()
+        .map(|(title, items)| RtSection { title: title.to_string(), impl_block: None, items })
+        .collect::<Vec<_>>();
+
+        let mut trait_impls: Vec<&RtImplBlock> = Vec::new();
+        for block in self.items.iter().filter_map(|i| i.impl_block.as_ref()) {
+            if block.trait_path.is_some() && !trait_impls.contains(&block) {
+                trait_impls.push(block);
+            }
+        }
+        for block in trait_impls {
+            sections.push(RtSection {
+                title: block.header(),
+                impl_block: Some(block),
+                items: select(&|i| i.impl_block.as_ref() == Some(block)),
+            });
+        }
+
+        sections.retain(|s| !s.items.is_empty());
+        sections
+    }
+}
+
+impl RtDocItem {
+    ///  Short title such as `open spec fn name` or `struct Name`.
+    pub fn title(&self) -> String {
+        match self.item_kind {
+            RtItemKind::Function => format!(
+                "{broadcast}{open}{kind} fn {name}",
+                broadcast = if self.is_broadcast { "broadcast " } else { "" },
+                open = if self.is_open { "open " } else { "" },
+                kind = self.kind.as_str(),
+                name = self.name,
+            ),
+            //  Plain `const` is exec; only ghost modes are spelled out
+            RtItemKind::Const if self.kind != RtFnKind::Exec => format!(
+                "{open}{kind} const {name}",
+                open = if self.is_open { "open " } else { "" },
+                kind = self.kind.as_str(),
+                name = self.name,
+            ),
+            _ => format!("{} {}", self.item_kind.as_str(), self.name),
+        }
+    }
+
+    ///  The signature as rendered, followed by `= value` for constants whose
+    ///  value is shown and `= Type` for type aliases.
+    pub fn declaration(&self) -> String {
+        match self.value.as_ref().or(self.aliased_type.as_ref()) {
+            Some(value) => format!("{} = {}", self.signature, value),
+            None => self.signature.clone(),
+        }
+    }
+
+    ///  The trait path if this item was declared in an `impl Trait for Type` block.
+    pub fn impl_trait(&self) -> Option<&str> {
+        self.impl_block.as_ref().and_then(|b| b.trait_path.as_deref())
+    }
+
+    ///  The trait methods of this item, split into (required, provided).
+    pub fn trait_methods(&self) -> (Vec<&RtDocItem>, Vec<&RtDocItem>) {
+        self.members
+            .iter()
+            .filter(|m| m.item_kind == RtItemKind::Function)
+            .partition(|m| !m.has_body)
+    }
+}
+
+impl RtImplBlock {
+    ///  Render the header without its where clause, e.g. `impl<T> View for Foo<T>`.
+    pub fn header(&self) -> String {
+        let generics = self.generics.as_deref().unwrap_or("");
+        match &self.trait_path {
+            Some(trait_path) => format!("impl{} {} for {}", generics, trait_path, self.self_ty),
+            None => format!("impl{} {}", generics, self.self_ty),
+        }
+    }
+}
+
+impl RtContract {
+    ///  The clause lists paired with their keyword, in rendering order.
+    pub fn clauses(&self) -> [(&'static str, &Vec<String>); 5] {
+        [
+            ("requires", &self.requires),
+            ("recommends", &self.recommends),
+            ("ensures", &self.ensures),
+            ("returns", &self.returns),
+            ("decreases", &self.decreases),
+        ]
+    }
+}
+
+impl RtVisibility {
+    ///  Mirrors `vis_rank`: higher is more visible.
+    pub fn rank(&self) -> u8 {
+        match self {
+            RtVisibility::Private => 0,
+            RtVisibility::PublicSuper | RtVisibility::PublicIn(_) => 1,
+            RtVisibility::PublicCrate => 2,
+            RtVisibility::Public => 3,
+        }
+    }
+
+    ///  The visibility as written in source, e.g. `pub(in crate::a)`.
+    pub fn as_string(&self) -> String {
+        match self {
+            RtVisibility::Public => "pub".to_string(),
+            RtVisibility::PublicCrate => "pub(crate)".to_string(),
+            RtVisibility::PublicSuper => "pub(super)".to_string(),
+            RtVisibility::PublicIn(path) => format!("pub(in {})", path),
+            RtVisibility::Private => String::new(),
+        }
+    }
+
+    ///  Parse a `--min-visibility` level: `public`, `crate`, `restricted`
+    ///  (`pub(super)` / `pub(in ...)`) or `private`.
+    pub fn from_level(level: &str) -> Option<RtVisibility> {
+        match level {
+            "public" => Some(RtVisibility::Public),
+            "crate" => Some(RtVisibility::PublicCrate),
+            "restricted" => Some(RtVisibility::PublicSuper),
+            "private" => Some(RtVisibility::Private),
+            _ => None,
+        }
+    }
+
+    ///  The `--min-visibility` level this visibility falls under.
+    pub fn level(&self) -> &'static str {
+        match self {
+            RtVisibility::Public => "public",
+            RtVisibility::PublicCrate => "crate",
+            RtVisibility::PublicSuper | RtVisibility::PublicIn(_) => "restricted",
+            RtVisibility::Private => "private",
         }
     }
 }
@@ -1,6 +1,8 @@
 use vstd::prelude::*;
 use crate::doc_item::*;
 
+use std::collections::HashMap;
+
 verus! {
 
 ///  Filters a sequence to keep only Public items.
@@ -42,6 +44,33 @@ pub proof fn lemma_filter_public_length(items: Seq<DocItem>)
     }
 }
 
+///  Filters a sequence to keep only items at least as visible as `min`.
+pub open spec fn filter_visible_spec(items: Seq<DocItem>, min: Visibility) -> Seq<DocItem>
+    decreases items.len(),
+{
+    if items.len() == 0 {
+        Seq::empty()
+    } else {
+        let rest = filter_visible_spec(items.skip(1), min);
+        if vis_rank(items[0].visibility) >= vis_rank(min) {
+            seq![items[0]].add(rest)
+        } else {
+            rest
+        }
+    }
+}
+
+///  All items in the visibility-filtered result are at least as visible as `min`.
+pub proof fn lemma_filter_visible_at_least(items: Seq<DocItem>, min: Visibility)
+    ensures
+        all_items_at_least(filter_visible_spec(items, min), min),
+    decreases items.len(),
+{
+    if items.len() > 0 {
+        lemma_filter_visible_at_least(items.skip(1), min);
+    }
+}
+
 ///  Insertion sort by line number (spec).
 ///  Proves: output is sorted by line number.
 pub open spec fn sort_by_line_spec(items: Seq<DocItem>) -> Seq<DocItem>
@@ -190,12 +219,38 @@ pub proof fn lemma_insert_kind_length(item: DocItem, sorted: Seq<DocItem>)
 } //  verus!
 
 ///  Build the runtime documentation output from a list of public items.
-///  Groups by module, sorts modules alphabetically, sorts items by kind within each module.
-pub fn build_doc_output(items: Vec<RtDocItem>) -> RtDocOutput {
+///  Groups by module, sorts modules alphabetically, sorts items by category and kind within each module.
+///  Module docs come from the `mod` declarations and `crate_doc` from the crate root; a
+///  documented module is listed even if it has no items of its own.
+pub fn build_doc_output(items: Vec<RtDocItem>, crate_doc: Option<String>) -> RtDocOutput {
     use std::collections::BTreeMap;
 
+    //  Trait impl blocks are not listed on their own; attach them to their traits
+    let (impls, mut items): (Vec<RtDocItem>, Vec<RtDocItem>) = items
+        .into_iter()
+        .partition(|item| item.item_kind == RtItemKind::Impl);
+    attach_trait_impls(&mut items, impls);
+    attach_models(&mut items);
+
+    //  `mod` declarations carry module docs but are not listed as items
+    let (decls, items): (Vec<RtDocItem>, Vec<RtDocItem>) = items
+        .into_iter()
+        .partition(|item| item.item_kind == RtItemKind::Module);
+    let mut docs: BTreeMap<String, String> = BTreeMap::new();
+    for decl in decls {
+        if let Some(doc) = decl.doc_comment {
+            docs.insert(format!("{}::{}", decl.module_path, decl.name), doc);
+        }
+    }
+    if let Some(doc) = crate_doc {
+        docs.insert("crate".to_string(), doc);
+    }
+
     //  Group items by module path
     let mut modules: BTreeMap<String, Vec<RtDocItem>> = BTreeMap::new();
+    for path in docs.keys() {
+        modules.insert(path.clone(), Vec::new());
+    }
     for item in items {
         modules.entry(item.module_path.clone()).or_default().push(item);
     }
@@ -203,14 +258,139 @@ pub fn build_doc_output(items: Vec<RtDocItem>) -> RtDocOutput {
     //  Build sorted modules
     let mut result: Vec<RtDocModule> = Vec::new();
     for (path, mut items) in modules {
-        //  Sort items: first by kind (spec, proof, exec), then by line number within kind
+        //  Sort items: types first, then functions by kind (spec, proof, exec),
+        //  then by line number within kind
         items.sort_by(|a, b| {
-            a.kind.ord().cmp(&b.kind.ord())
+            a.item_kind.ord().cmp(&b.item_kind.ord())
+                .then(a.kind.ord().cmp(&b.kind.ord()))
                 .then(a.line_number.cmp(&b.line_number))
         });
-        result.push(RtDocModule { path, items });
+        assign_anchors(&mut items);
+        let doc = docs.remove(&path);
+        result.push(RtDocModule { path, doc, items });
     }
 
     //  BTreeMap already gives us alphabetical module order
     RtDocOutput { modules: result }
 }
+
+///  Give each item of a module page, and each trait method rendered inside
+///  one, an anchor id `kind.name`, with spaces in the kind as `-` (as in
+///  `broadcast-group.name`). Repeats get `-2`, `-3`, ... in order.
+fn assign_anchors(items: &mut [RtDocItem]) {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    let mut assign = |item: &mut RtDocItem| {
+        let base = format!("{}.{}", item.item_kind.as_str().replace(' ', "-"), item.name);
+        let count = counts.entry(base.clone()).or_insert(0);
+        *count += 1;
+        item.anchor = if *count == 1 { base } else { format!("{}-{}", base, count) };
+    };
+    for item in items {
+        assign(item);
+        for method in item.members.iter_mut().filter(|m| m.item_kind == RtItemKind::Function) {
+            assign(method);
+        }
+    }
+}
+
+///  Attach each `impl Trait for Type` block to the trait its path resolves to
+///  (see `scope::resolve_impls`). Impls of traits outside the crate, and of
+///  same-named traits elsewhere, are not attached.
+fn attach_trait_impls(items: &mut [RtDocItem], impls: Vec<RtDocItem>) {
+    for item in items.iter_mut().filter(|i| i.item_kind == RtItemKind::Trait) {
+        let path = format!("{}::{}", item.module_path, item.name);
+        item.impls = impls
+            .iter()
+            .filter(|imp| imp.impl_block.as_ref().and_then(|b| b.trait_target.as_deref()) == Some(path.as_str()))
+            .cloned()
+            .collect();
+    }
+}
+
+///  Attach the `View` and `DeepView` impls of each struct or enum as its
+///  abstract models. The impl's self type must resolve to a crate type (see
+///  `scope::resolve_impls`); models of other impls are dropped.
+fn attach_models(items: &mut [RtDocItem]) {
+    let mut models: Vec<(String, RtAbstractModel)> = Vec::new();
+    for item in items.iter() {
+        let Some(block) = item.impl_block.as_ref() else {
+            continue;
+        };
+        let Some(ref owner) = block.self_target else {
+            continue;
+        };
+        let trait_name = match item.impl_trait().map(trait_simple_name) {
+            Some(name @ ("View" | "DeepView")) => name,
+            _ => continue,
+        };
+        let header = block.header();
+        let index = match models.iter().position(|(path, m)| path == owner && m.header == header) {
+            Some(index) => index,
+            None => {
+                models.push((owner.clone(), RtAbstractModel {
+                    trait_name: trait_name.to_string(),
+                    header,
+                    file_path: item.file_path.clone(),
+                    line_number: item.line_number,
+                    ..Default::default()
+                }));
+                models.len() - 1
+            }
+        };
+        let model = &mut models[index].1;
+        model.line_number = model.line_number.min(item.line_number);
+        match (item.item_kind, item.name.as_str()) {
+            (RtItemKind::AssocType, "V") => model.model_type = item.aliased_type.clone(),
+            (RtItemKind::Function, "view" | "deep_view") => model.view_body = item.spec_body.clone(),
+            _ => {}
+        }
+    }
+
+    for (path, model) in models {
+        if let Some(index) = type_owner(items, &path) {
+            items[index].models.push(model);
+        }
+    }
+}
+
+///  Attach the invariants stated by methods of inherent impls (see
+///  `RtDocItem::invariant_kind`) to the structs and enums their impls resolve
+///  to, like abstract models. Run this before visibility filtering: invariant
+///  functions are usually private, but what they state holds for every value.
+pub fn attach_invariants(items: &mut [RtDocItem]) {
+    let invariants: Vec<(String, RtInvariant)> = items
+        .iter()
+        .filter_map(|item| {
+            let invariant = RtInvariant {
+                kind: item.invariant_kind?,
+                name: item.name.clone(),
+                signature: item.signature.clone(),
+                body: item.spec_body.clone(),
+                doc_comment: item.doc_comment.clone(),
+                file_path: item.file_path.clone(),
+                line_number: item.line_number,
+            };
+            let owner = item.impl_block.as_ref()?.self_target.clone()?;
+            Some((owner, invariant))
+        })
+        .collect();
+
+    for (path, invariant) in invariants {
+        if let Some(index) = type_owner(items, &path) {
+            items[index].invariants.push(invariant);
+        }
+    }
+}
+
+///  The struct or enum at the full path `path`.
+fn type_owner(items: &[RtDocItem], path: &str) -> Option<usize> {
+    items
+        .iter()
+        .position(|i| i.item_kind.is_type() && format!("{}::{}", i.module_path, i.name) == path)
+}
+
+///  Strip the module path and generic arguments from a trait or type reference.
+pub fn trait_simple_name(path: &str) -> &str {
+    let base = path.split('<').next().unwrap_or(path);
+    base.rsplit("::").next().unwrap_or(base).trim()
+}
@@ -0,0 +1,318 @@
+use crate::doc_item::*;
+use crate::html;
+use crate::json::Json;
+use crate::links::{self, LinkIndex};
+
+use std::collections::HashMap;
+
+///  Version of the JSON schema emitted by `to_json`, bumped independently of
+///  the doc output's `json::SCHEMA_VERSION` under the same rules.
+pub const LEMMA_GRAPH_SCHEMA_VERSION: u64 = 1;
+
+///  A documented proof function in the lemma graph.
+#[derive(Debug, Clone)]
+pub struct LemmaNode {
+    ///  Full path, e.g. `crate::grouping::lemma_seq_char_le_transitive`.
+    pub path: String,
+    pub name: String,
+    ///  Module page the lemma is documented on, and its anchor there.
+    pub page: String,
+    pub anchor: String,
+    pub file_path: String,
+    pub line_number: usize,
+}
+
+///  Calls between the documented proof functions of a crate, including calls
+///  made through proof functions hidden at the documented visibility.
+#[derive(Debug, Clone, Default)]
+pub struct LemmaGraph {
+    pub nodes: Vec<LemmaNode>,
+    ///  `(caller, callee)` indices into `nodes`, without repeats. Recursive
+    ///  lemmas have an edge to themselves.
+    pub edges: Vec<(usize, usize)>,
+}
+
+///  Build the lemma graph of the proof functions in `output` (including trait
+///  methods). Calls are resolved like intra-doc links over `all`, the output
+///  built from every extracted item, so a call through lemmas hidden from
+///  `output` becomes an edge to the visible lemmas they reach. Calls to
+///  anything but a proof function are dropped.
+pub fn build_lemma_graph(output: &RtDocOutput, all: &RtDocOutput) -> LemmaGraph {
+    let (mut graph, origins) = direct_graph(output);
+    let (full, _) = direct_graph(all);
+
+    //  Visible lemmas by where they are defined; a re-exported lemma can be
+    //  listed at more than one path
+    let mut visible: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (index, origin) in origins.iter().enumerate() {
+        visible.entry(origin.as_str()).or_default().push(index);
+    }
+    let by_path: HashMap<&str, usize> =
+        full.nodes.iter().enumerate().rev().map(|(index, node)| (node.path.as_str(), index)).collect();
+
+    for (caller, origin) in origins.iter().enumerate() {
+        let Some(&start) = by_path.get(origin.as_str()) else {
+            continue;
+        };
+        let mut stack = vec![start];
+        let mut seen = vec![false; full.nodes.len()];
+        while let Some(from) = stack.pop() {
+            for &(_, to) in full.edges.iter().filter(|&&(f, _)| f == from) {
+                if std::mem::replace(&mut seen[to], true) {
+                    continue;
+                }
+                match visible.get(full.nodes[to].path.as_str()) {
+                    Some(callees) => {
+                        for &callee in callees {
+                            if !graph.edges.contains(&(caller, callee)) {
+                                graph.edges.push((caller, callee));
+                            }
+                        }
+                    }
+                    None => stack.push(to),
+                }
+            }
+        }
+    }
+
+    graph
+}
+
+///  The proof functions of `output` and the calls between them, with the
+///  path each was defined at (differing from the node's for re-exports).
+fn direct_graph(output: &RtDocOutput) -> (LemmaGraph, Vec<String>) {
+    let index = LinkIndex::new(output);
+
+    //  Proof functions with the trait they belong to, if any
+    let mut proofs: Vec<(&RtDocItem, Option<String>, &str)> = Vec::new();
+    for module in &output.modules {
+        for item in &module.items {
+            let owner = links::trait_path(item);
+            proofs.push((item, None, &module.path));
+            for member in &item.members {
+                proofs.push((member, owner.clone(), &module.path));
+            }
+        }
+    }
+    proofs.retain(|(item, _, _)| item.item_kind == RtItemKind::Function && item.kind == RtFnKind::Proof);
+
+    let mut graph = LemmaGraph::default();
+    let mut origins = Vec::new();
+    let mut by_path: HashMap<String, usize> = HashMap::new();
+    for (item, _, page) in &proofs {
+        let path = format!("{}::{}", item.module_path, item.name);
+        by_path.entry(path.clone()).or_insert(graph.nodes.len());
+        origins.push(item.reexport_of.clone().unwrap_or_else(|| path.clone()));
+        graph.nodes.push(LemmaNode {
+            path,
+            name: item.name.clone(),
+            page: page.to_string(),
+            anchor: item.anchor.clone(),
+            file_path: item.file_path.clone(),
+            line_number: item.line_number,
+        });
+    }
+
+    for (caller, (item, owner, _)) in proofs.iter().enumerate() {
+        for call in &item.body_calls {
+            let Some(&callee) = index.resolve_in(call, item, owner.as_deref()).and_then(|p| by_path.get(p)) else {
+                continue;
+            };
+            if !graph.edges.contains(&(caller, callee)) {
+                graph.edges.push((caller, callee));
+            }
+        }
+    }
+
+    (graph, origins)
+}
+
+///  Quote a string as a Graphviz id.
+fn dot_id(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+///  Render the graph as Graphviz DOT, one cluster per module page.
+pub fn to_dot(graph: &LemmaGraph) -> String {
+    let mut dot = String::from("digraph lemmas {\n");
+    dot.push_str("  rankdir=LR;\n  node [shape=box, fontname=\"monospace\"];\n");
+
+    let mut pages: Vec<&str> = graph.nodes.iter().map(|n| n.page.as_str()).collect();
+    pages.dedup();
+    for page in pages {
+        dot.push_str(&format!("  subgraph {} {{\n", dot_id(&format!("cluster_{}", page))));
+        dot.push_str(&format!("    label={};\n", dot_id(page)));
+        for node in graph.nodes.iter().filter(|n| n.page == page) {
+            dot.push_str(&format!("    {} [label={}];\n", dot_id(&node.path), dot_id(&node.name)));
+        }
+        dot.push_str("  }\n");
+    }
+
+    for &(from, to) in &graph.edges {
+        dot.push_str(&format!("  {} -> {};\n", dot_id(&graph.nodes[from].path), dot_id(&graph.nodes[to].path)));
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+///  Render the graph as JSON: `{ schema_version, nodes: [...], edges: [{ from, to }] }`
+///  with edges given by node path.
+pub fn to_json(graph: &LemmaGraph) -> String {
+    let nodes = graph
+        .nodes
+        .iter()
+        .map(|node| {
+            Json::object(vec![
+                ("path", Json::string(&node.path)),
+                ("name", Json::string(&node.name)),
+                ("module", Json::string(&node.page)),
+                ("file", Json::string(&node.file_path)),
+                ("line", Json::Number(node.line_number as u64)),
+            ])
+        })
+        .collect();
+    let edges = graph
+        .edges
+        .iter()
+        .map(|&(from, to)| {
+            Json::object(vec![
+                ("from", Json::string(&graph.nodes[from].path)),
+                ("to", Json::string(&graph.nodes[to].path)),
+            ])
+        })
+        .collect();
+
+    Json::object(vec![
+        ("schema_version", Json::Number(LEMMA_GRAPH_SCHEMA_VERSION)),
+        ("nodes", Json::Array(nodes)),
+        ("edges", Json::Array(edges)),
+    ])
+    .to_pretty_string()
+}
+
+const NODE_HEIGHT: usize = 24;
+const ROW_GAP: usize = 12;
+const COLUMN_GAP: usize = 60;
+const CHAR_WIDTH: usize = 8;
+
+///  Render the lemmas of one module page, plus the lemmas elsewhere that they
+///  call or are called by, as an inline SVG with callers left of callees.
+///  Returns None if the page has no lemmas.
+pub fn module_svg(graph: &LemmaGraph, page: &str) -> Option<String> {
+    let local: Vec<usize> = (0..graph.nodes.len()).filter(|&n| graph.nodes[n].page == page).collect();
+    if local.is_empty() {
+        return None;
+    }
+    let mut shown = local.clone();
+    for &(from, to) in &graph.edges {
+        for (a, b) in [(from, to), (to, from)] {
+            if local.contains(&a) && !shown.contains(&b) {
+                shown.push(b);
+            }
+        }
+    }
+    let edges: Vec<(usize, usize)> = graph
+        .edges
+        .iter()
+        .copied()
+        .filter(|&(from, to)| from != to && shown.contains(&from) && shown.contains(&to))
+        .collect();
+
+    //  Column = longest chain of callers, capped so cycles terminate
+    let mut column: HashMap<usize, usize> = shown.iter().map(|&n| (n, 0)).collect();
+    for _ in 0..shown.len() {
+        for &(from, to) in &edges {
+            let next = (column[&from] + 1).min(shown.len());
+            if column[&to] < next {
+                column.insert(to, next);
+            }
+        }
+    }
+
+    let width = shown.iter().map(|&n| graph.nodes[n].name.len()).max().unwrap_or(0) * CHAR_WIDTH + 16;
+    let mut rows: HashMap<usize, usize> = HashMap::new();
+    let mut position: HashMap<usize, (usize, usize)> = HashMap::new();
+    for &n in &shown {
+        let row = rows.entry(column[&n]).or_insert(0);
+        position.insert(n, (column[&n] * (width + COLUMN_GAP), *row * (NODE_HEIGHT + ROW_GAP)));
+        *row += 1;
+    }
+    let columns = column.values().max().unwrap_or(&0) + 1;
+    let svg_width = columns * (width + COLUMN_GAP) - COLUMN_GAP + 2;
+    let svg_height = rows.values().max().unwrap_or(&1) * (NODE_HEIGHT + ROW_GAP) - ROW_GAP + 2;
+
+    let mut svg = format!(
+        "<svg class=\"lemma-graph\" width=\"{w}\" height=\"{h}\" viewBox=\"-1 -1 {w} {h}\" \
+         xmlns=\"http://www.w3.org/2000/svg\">\n\
+         <defs><marker id=\"arrow\" viewBox=\"0 0 10 10\" refX=\"10\" refY=\"5\" markerWidth=\"6\" \
+         markerHeight=\"6\" orient=\"auto\"><path d=\"M0,0 L10,5 L0,10 z\"/></marker></defs>\n",
+        w = svg_width,
+        h = svg_height,
+    );
+    for &(from, to) in &edges {
+        let (x1, y1) = position[&from];
+        let (x2, y2) = position[&to];
+        svg.push_str(&format!(
+            "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" marker-end=\"url(#arrow)\"/>\n",
+            x1 + width,
+            y1 + NODE_HEIGHT / 2,
+            x2,
+            y2 + NODE_HEIGHT / 2,
+        ));
+    }
+    for &n in &shown {
+        let node = &graph.nodes[n];
+        let (x, y) = position[&n];
+        let class = if node.page == page { "local" } else { "external" };
+        let href = format!("{}#{}", html::module_file_name(&node.page), node.anchor);
+        svg.push_str(&format!(
+            "<a href=\"{href}\"><title>{path}</title><rect class=\"{class}\" x=\"{x}\" y=\"{y}\" width=\"{w}\" \
+             height=\"{h}\" rx=\"3\"/><text x=\"{tx}\" y=\"{ty}\">{name}</text></a>\n",
+            href = html::escape(&href),
+            path = html::escape(&node.path),
+            class = class,
+            x = x,
+            y = y,
+            w = width,
+            h = NODE_HEIGHT,
+            tx = x + 8,
+            ty = y + 16,
+            name = html::escape(&node.name),
+        ));
+    }
+    svg.push_str("</svg>\n");
+    Some(svg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(lemmas: &[(&str, &[&str])]) -> RtDocOutput {
+        let items = lemmas
+            .iter()
+            .map(|(name, calls)| RtDocItem {
+                name: name.to_string(),
+                module_path: "crate".to_string(),
+                kind: RtFnKind::Proof,
+                anchor: format!("fn.{}", name),
+                body_calls: calls.iter().map(|c| c.to_string()).collect(),
+                ..Default::default()
+            })
+            .collect();
+        RtDocOutput { modules: vec![RtDocModule { path: "crate".to_string(), doc: None, items }] }
+    }
+
+    #[test]
+    fn calls_through_hidden_lemmas_are_collapsed() {
+        //  a -> hidden -> c, and c -> c directly; `spec_f` is not a lemma
+        let all = output(&[("a", &["hidden", "spec_f"]), ("hidden", &["c"]), ("c", &["c"])]);
+        let visible = output(&[("a", &["hidden", "spec_f"]), ("c", &["c"])]);
+
+        let graph = build_lemma_graph(&visible, &all);
+        let names: Vec<&str> = graph.nodes.iter().map(|n| n.name.as_str()).collect();
+        assert_eq!(names, vec!["a", "c"]);
+        //  Direct calls come first, then those collapsed through hidden lemmas
+        assert_eq!(graph.edges, vec![(1, 1), (0, 1)]);
+    }
+}
@@ -0,0 +1,878 @@
+use crate::contract::collapse_whitespace;
+use crate::doc_item::*;
+use crate::graph::{self, LemmaGraph};
+use crate::json::Json;
+use crate::trust;
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+///  Keywords highlighted in signatures.
+const KEYWORDS: [&str; 30] = [
+    "pub", "fn", "spec", "proof", "exec", "open", "closed", "tracked", "ghost", "broadcast", "struct",
+    "enum", "trait", "impl", "for", "where", "mut", "const", "static", "type", "mod", "use",
+    "as", "in", "self", "Self", "crate", "super", "dyn", "unsafe",
+];
+
+///  Stylesheet shared by all pages; written next to them as `style.css`.
+const STYLE: &str = "\
+body { font-family: system-ui, sans-serif; margin: 0; display: flex; color: #222; }
+nav { width: 18rem; padding: 1rem; background: #f5f5f5; height: 100vh; overflow-y: auto; position: sticky; top: 0; box-sizing: border-box; }
+nav ul { list-style: none; padding-left: 1rem; margin: 0; }
+main { flex: 1; padding: 1rem 2rem; max-width: 60rem; }
+a { color: #2a6ebb; text-decoration: none; }
+a:hover { text-decoration: underline; }
+code, pre { font-family: ui-monospace, monospace; font-size: 0.9rem; }
+pre.sig { background: #f8f8f8; border-left: 3px solid #2a6ebb; padding: 0.5rem; overflow-x: auto; white-space: pre-wrap; }
+section.item { border-top: 1px solid #ddd; padding: 0.5rem 0; }
+section.item section.item { margin-left: 1.5rem; }
+.item h4 { margin: 0.3rem 0; display: flex; justify-content: space-between; }
+.src { font-weight: normal; font-size: 0.8rem; }
+.reexport { font-style: italic; color: #666; }
+.doc pre { background: #f8f8f8; padding: 0.5rem; overflow-x: auto; }
+dl.contract dt { font-weight: bold; font-style: italic; }
+.used-in { font-size: 0.85rem; color: #666; }
+.model { border-left: 3px solid #4a7ab5; padding-left: 0.8rem; margin: 0.6rem 0; }
+.trust { font-size: 0.75rem; font-weight: normal; color: #8a4b00; background: #fff3e0; border: 1px solid #f0c080; border-radius: 3px; padding: 0 0.3rem; }
+.lemma-graph { display: block; max-width: 100%; overflow: visible; }
+.lemma-graph rect.local { fill: #eef4fb; stroke: #2a6ebb; }
+.lemma-graph rect.external { fill: #fff; stroke: #999; stroke-dasharray: 3 2; }
+.lemma-graph text { font-family: ui-monospace, monospace; font-size: 12px; fill: #222; }
+.lemma-graph line { stroke: #888; }
+.kw { color: #8959a8; font-weight: bold; }
+.ty { color: #3e999f; }
+.st { color: #718c00; }
+.nu { color: #f5871f; }
+.search input { width: 100%; box-sizing: border-box; margin-bottom: 0.3rem; }
+.search select { width: 49%; }
+.search ul { padding-left: 0; margin: 0.5rem 0; }
+.search li { margin: 0.3rem 0; font-size: 0.85rem; }
+.search li span { display: block; color: #666; }
+";
+
+///  Search box behaviour, written as `search.js`. The index is loaded from
+///  `search-index.js` as a global so search also works over `file://`.
+const SEARCH_JS: &str = r#"(function () {
+  var input = document.getElementById("search-input");
+  var kind = document.getElementById("search-kind");
+  var openness = document.getElementById("search-open");
+  var results = document.getElementById("search-results");
+  var index = window.SEARCH_INDEX || [];
+
+  function score(entry, terms) {
+    var name = entry.name.toLowerCase();
+    var text = (entry.path + "::" + entry.name + " " + entry.doc + " " + entry.contract).toLowerCase();
+    var total = 0;
+    for (var i = 0; i < terms.length; i++) {
+      var t = terms[i];
+      if (name === t) continue;
+      if (name.indexOf(t) === 0) total += 1;
+      else if (name.indexOf(t) >= 0) total += 2;
+      else if (text.indexOf(t) >= 0) total += 4;
+      else return -1;
+    }
+    return total;
+  }
+
+  function update() {
+    var terms = input.value.toLowerCase().split(/\s+/).filter(Boolean);
+    results.innerHTML = "";
+    if (terms.length === 0) return;
+    var matches = [];
+    index.forEach(function (entry) {
+      if (kind.value !== "all" && entry.fn_kind !== kind.value) return;
+      if (openness.value === "open" && !entry.open) return;
+      if (openness.value === "closed" && (entry.open || entry.fn_kind !== "spec")) return;
+      var s = score(entry, terms);
+      if (s >= 0) matches.push([s, entry]);
+    });
+    matches.sort(function (a, b) { return a[0] - b[0] || a[1].name.length - b[1].name.length; });
+    matches.slice(0, 50).forEach(function (m) {
+      var entry = m[1];
+      var li = document.createElement("li");
+      var a = document.createElement("a");
+      a.href = entry.url;
+      a.textContent = (entry.fn_kind ? entry.fn_kind + " " : "") + entry.kind + " " + entry.name;
+      li.appendChild(a);
+      var info = document.createElement("span");
+      info.textContent = entry.path + (entry.doc ? " — " + entry.doc : "");
+      li.appendChild(info);
+      results.appendChild(li);
+    });
+  }
+
+  input.addEventListener("input", update);
+  kind.addEventListener("change", update);
+  openness.addEventListener("change", update);
+})();
+"#;
+
+///  Search form placed at the top of every page's navigation.
+const SEARCH_FORM: &str = "\
+<div class=\"search\">
+<input id=\"search-input\" type=\"search\" placeholder=\"Search names, docs, contracts\" autocomplete=\"off\">
+<select id=\"search-kind\"><option value=\"all\">any mode</option><option value=\"spec\">spec</option>\
+<option value=\"proof\">proof</option><option value=\"exec\">exec</option></select>
+<select id=\"search-open\"><option value=\"all\">open or closed</option><option value=\"open\">open</option>\
+<option value=\"closed\">closed</option></select>
+<ul id=\"search-results\"></ul>
+</div>
+";
+
+///  Per-page rendering state.
+struct PageState<'a> {
+    src_prefix: &'a str,
+    ///  File name of the page, used to build search result links.
+    file_name: String,
+    ///  Search index entries for the items rendered so far.
+    search: Vec<Json>,
+}
+
+///  Write a static HTML site: `index.html` with the module tree, one page per
+///  module, `style.css`, and the search script with its index. Pages use no
+///  external assets. `src_prefix` is prepended to file paths to form source links.
+///  With a `lemma_graph`, each page with lemmas gets a drawing of their dependencies.
+pub fn write_site(
+    output: &RtDocOutput,
+    out_dir: &Path,
+    src_prefix: &str,
+    lemma_graph: Option<&LemmaGraph>,
+) -> std::io::Result<()> {
+    std::fs::create_dir_all(out_dir)?;
+    std::fs::write(out_dir.join("style.css"), STYLE)?;
+    std::fs::write(out_dir.join("search.js"), SEARCH_JS)?;
+
+    let nav = format_module_tree(output);
+    std::fs::write(out_dir.join("index.html"), format_index(output, &nav))?;
+
+    let mut search = Vec::new();
+    for module in &output.modules {
+        let page = format_module_page(output, module, &nav, src_prefix, lemma_graph, &mut search);
+        std::fs::write(out_dir.join(module_file_name(&module.path)), page)?;
+    }
+
+    let index = format!("window.SEARCH_INDEX = {};", Json::Array(search).to_pretty_string().trim_end());
+    std::fs::write(out_dir.join("search-index.js"), index + "\n")?;
+
+    Ok(())
+}
+
+///  File name of a module's page, e.g. `crate.foo.bar.html`.
+pub fn module_file_name(module_path: &str) -> String {
+    format!("{}.html", module_path.replace("::", "."))
+}
+
+///  Escape text for use in HTML content and attribute values.
+pub fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+///  Wrap a page body with the shared head, navigation and stylesheet.
+fn page(title: &str, nav: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{title}</title>\n<link rel=\"stylesheet\" href=\"style.css\">\n</head>\n\
+         <body>\n<nav>\n<p><a href=\"index.html\">API Reference</a></p>\n{search}{nav}</nav>\n\
+         <main>\n{body}</main>\n<script src=\"search-index.js\"></script>\n\
+         <script src=\"search.js\"></script>\n</body>\n</html>\n",
+        title = escape(title),
+        search = SEARCH_FORM,
+        nav = nav,
+        body = body,
+    )
+}
+
+///  A node of the module tree; `has_page` is false for path prefixes that
+///  have no documented items of their own.
+#[derive(Default)]
+struct TreeNode {
+    has_page: bool,
+    children: BTreeMap<String, TreeNode>,
+}
+
+///  Render the module tree as nested lists linking to each module page.
+fn format_module_tree(output: &RtDocOutput) -> String {
+    let mut root = TreeNode::default();
+    for module in &output.modules {
+        let mut node = &mut root;
+        for segment in module.path.split("::") {
+            node = node.children.entry(segment.to_string()).or_default();
+        }
+        node.has_page = true;
+    }
+
+    let mut html = String::new();
+    format_tree_node(&mut html, &root, "");
+    html
+}
+
+fn format_tree_node(html: &mut String, node: &TreeNode, prefix: &str) {
+    if node.children.is_empty() {
+        return;
+    }
+    html.push_str("<ul>\n");
+    for (name, child) in &node.children {
+        let path = if prefix.is_empty() {
+            name.clone()
+        } else {
+            format!("{}::{}", prefix, name)
+        };
+        if child.has_page {
+            html.push_str(&format!(
+                "<li><a href=\"{}\">{}</a>",
+                module_file_name(&path),
+                escape(name)
+            ));
+        } else {
+            html.push_str(&format!("<li>{}", escape(name)));
+        }
+        format_tree_node(html, child, &path);
+        html.push_str("</li>\n");
+    }
+    html.push_str("</ul>\n");
+}
+
+fn format_index(output: &RtDocOutput, nav: &str) -> String {
+    let mut body = String::new();
+    body.push_str("<h1>API Reference</h1>\n<p><em>Auto-generated by verus-docgenerator</em></p>\n");
+    if let Some(intro) = output.intro() {
+        body.push_str(&format!("<div class=\"doc\">{}</div>\n", format_doc(intro, 1)));
+    }
+    body.push_str("<h2>Modules</h2>\n<ul>\n");
+    for module in &output.modules {
+        body.push_str(&format!(
+            "<li><a href=\"{}\"><code>{}</code></a> ({} items)</li>\n",
+            module_file_name(&module.path),
+            escape(&module.path),
+            module.items.len()
+        ));
+    }
+    body.push_str("</ul>\n");
+    page("API Reference", nav, &body)
+}
+
+///  Render a module page, appending its items' search entries to `search`.
+fn format_module_page(
+    output: &RtDocOutput,
+    module: &RtDocModule,
+    nav: &str,
+    src_prefix: &str,
+    lemma_graph: Option<&LemmaGraph>,
+    search: &mut Vec<Json>,
+) -> String {
+    let mut body = String::new();
+    body.push_str(&format!("<h1>{}</h1>\n", format_breadcrumbs(output, &module.path)));
+    if let Some(ref doc) = module.doc {
+        body.push_str(&format!("<div class=\"doc\">{}</div>\n", format_doc(doc, 1)));
+    }
+
+    let mut state = PageState {
+        src_prefix,
+        file_name: module_file_name(&module.path),
+        search: Vec::new(),
+    };
+    for section in module.sections() {
+        let title = match section.impl_block {
+            Some(_) => format!("<code>{}</code>", highlight(&section.title)),
+            None => escape(&section.title),
+        };
+        body.push_str(&format!("<h2>{}</h2>\n", title));
+        for item in section.items {
+            format_item(&mut body, item, &mut state);
+        }
+    }
+
+    if let Some(svg) = lemma_graph.and_then(|g| graph::module_svg(g, &module.path)) {
+        body.push_str("<h2>Lemma dependencies</h2>\n");
+        body.push_str(&svg);
+    }
+
+    search.append(&mut state.search);
+    page(&module.path, nav, &body)
+}
+
+///  Render `crate::a::b` with each ancestor that has a page linked.
+fn format_breadcrumbs(output: &RtDocOutput, path: &str) -> String {
+    let segments: Vec<&str> = path.split("::").collect();
+    let mut parts = Vec::new();
+    for i in 0..segments.len() {
+        let prefix = segments[..=i].join("::");
+        let has_page = output.modules.iter().any(|m| m.path == prefix);
+        if has_page && i + 1 < segments.len() {
+            parts.push(format!("<a href=\"{}\">{}</a>", module_file_name(&prefix), escape(segments[i])));
+        } else {
+            parts.push(escape(segments[i]));
+        }
+    }
+    format!("<code>{}</code>", parts.join("::"))
+}
+
+///  Render one item (and, for traits, its members) as a `<section>`, and
+///  record it in the page's search entries.
+fn format_item(html: &mut String, item: &RtDocItem, state: &mut PageState) {
+    let anchor = &item.anchor;
+    state.search.push(search_entry(item, &format!("{}#{}", state.file_name, anchor)));
+
+    html.push_str(&format!("<section class=\"item\" id=\"{}\">\n", escape(anchor)));
+    html.push_str(&format!(
+        "<h4><a href=\"#{anchor}\"><code>{title}</code></a>{badges} <span class=\"src\">{link}</span></h4>\n",
+        anchor = escape(anchor),
+        title = highlight(&item.title()),
+        badges = trust_badges(item),
+        link = source_link(&item.file_path, item.line_number, state.src_prefix),
+    ));
+
+    if let Some(ref original) = item.reexport_of {
+        html.push_str(&format!("<p class=\"reexport\">Re-exported from <code>{}</code></p>\n", escape(original)));
+    }
+
+    if !item.signature.is_empty() {
+        html.push_str(&format!("<pre class=\"sig\"><code>{}</code></pre>\n", highlight_with_links(&item.declaration(), &item.type_refs, false)));
+    }
+
+    if let Some(ref doc) = item.doc_comment {
+        html.push_str(&format!("<div class=\"doc\">{}</div>\n", format_doc(doc, 4)));
+    }
+
+    format_models(html, item);
+    format_invariants(html, item);
+    format_contract(html, item);
+    format_broadcast(html, item);
+    format_type_members(html, item);
+    format_trait_members(html, item, state);
+
+    html.push_str("</section>\n");
+}
+
+///  Badges for the kinds of trust an item relies on, each titled with the
+///  lines where it occurs.
+fn trust_badges(item: &RtDocItem) -> String {
+    trust::summary(&item.trust)
+        .into_iter()
+        .map(|(kind, count)| {
+            let lines: Vec<String> = item
+                .trust
+                .iter()
+                .filter(|site| site.kind == kind)
+                .map(|site| format!("{}:{}", item.file_path, site.line_number))
+                .collect();
+            format!(
+                " <span class=\"trust\" title=\"{title}\">{kind}{count}</span>",
+                title = escape(&lines.join(", ")),
+                kind = kind.as_str(),
+                count = if count > 1 { format!(" ×{}", count) } else { String::new() },
+            )
+        })
+        .collect()
+}
+
+///  Search index entry for an item rendered at `url`.
+fn search_entry(item: &RtDocItem, url: &str) -> Json {
+    let is_fn = item.item_kind == RtItemKind::Function;
+    let contract: Vec<String> = item
+        .contract
+        .clauses()
+        .iter()
+        .filter(|(_, clauses)| !clauses.is_empty())
+        .map(|(keyword, clauses)| format!("{} {}", keyword, clauses.join(", ")))
+        .collect();
+
+    Json::object(vec![
+        ("name", Json::string(&item.name)),
+        ("path", Json::string(&item.module_path)),
+        ("kind", Json::string(item.item_kind.as_str())),
+        ("fn_kind", if is_fn { Json::string(item.kind.as_str()) } else { Json::Null }),
+        ("open", Json::Bool(item.is_open)),
+        ("doc", Json::string(&item.doc_comment.as_deref().map(first_sentence).unwrap_or_default())),
+        ("contract", Json::string(&contract.join("; "))),
+        ("url", Json::string(url)),
+    ])
+}
+
+///  The first sentence of a doc comment: text up to the first `. ` or blank
+///  line, on one line.
+fn first_sentence(doc: &str) -> String {
+    let paragraph = collapse_whitespace(doc.split("\n\n").next().unwrap_or(doc));
+    match paragraph.find(". ") {
+        Some(end) => paragraph[..=end].to_string(),
+        None => paragraph,
+    }
+}
+
+///  Format a `file:line` link to an item's source location.
+fn source_link(file_path: &str, line: usize, src_prefix: &str) -> String {
+    let sep = if src_prefix.is_empty() || src_prefix.ends_with('/') { "" } else { "/" };
+    format!(
+        "<a href=\"{prefix}{sep}{file}#L{line}\">{file}:{line}</a>",
+        prefix = escape(src_prefix),
+        sep = sep,
+        file = escape(file_path),
+        line = line,
+    )
+}
+
+///  Render a Markdown doc comment: paragraphs, `#` headings (demoted below
+///  the enclosing `<h{heading_level}>`), bullet and numbered lists, and fenced
+///  code blocks, which are highlighted unless tagged with a non-Rust language.
+fn format_doc(doc: &str, heading_level: usize) -> String {
+    let mut html = String::new();
+    let mut paragraph: Vec<&str> = Vec::new();
+    let mut list: Vec<String> = Vec::new();
+    let mut list_tag = "ul";
+    let mut lines = doc.lines();
+
+    while let Some(line) = lines.next() {
+        let trimmed = line.trim();
+        if let Some(lang) = trimmed.strip_prefix("```").or_else(|| trimmed.strip_prefix("~~~")) {
+            flush_paragraph(&mut html, &mut paragraph);
+            flush_list(&mut html, &mut list, list_tag);
+            let fence = &trimmed[..3];
+            let code: Vec<&str> = lines.by_ref().take_while(|l| !l.trim_start().starts_with(fence)).collect();
+            let code = code.join("\n");
+            let lang = lang.trim();
+            let body = if lang.is_empty() || lang.contains("rust") || lang.contains("verus") {
+                highlight(&code)
+            } else {
+                escape(&code)
+            };
+            html.push_str(&format!("<pre><code>{}</code></pre>\n", body));
+        } else if trimmed.is_empty() {
+            flush_paragraph(&mut html, &mut paragraph);
+            flush_list(&mut html, &mut list, list_tag);
+        } else if let Some((level, text)) = heading(trimmed) {
+            flush_paragraph(&mut html, &mut paragraph);
+            flush_list(&mut html, &mut list, list_tag);
+            let level = (level + heading_level).min(6);
+            html.push_str(&format!("<h{level}>{}</h{level}>\n", format_inline(text), level = level));
+        } else if let Some((tag, text)) = list_marker(trimmed) {
+            flush_paragraph(&mut html, &mut paragraph);
+            if !list.is_empty() && tag != list_tag {
+                flush_list(&mut html, &mut list, list_tag);
+            }
+            list_tag = tag;
+            list.push(text.to_string());
+        } else if let Some(last) = list.last_mut() {
+            //  Continuation of the previous list entry
+            last.push(' ');
+            last.push_str(trimmed);
+        } else {
+            paragraph.push(trimmed);
+        }
+    }
+
+    flush_paragraph(&mut html, &mut paragraph);
+    flush_list(&mut html, &mut list, list_tag);
+    html
+}
+
+fn flush_paragraph(html: &mut String, paragraph: &mut Vec<&str>) {
+    if !paragraph.is_empty() {
+        html.push_str(&format!("<p>{}</p>\n", format_inline(&paragraph.join(" "))));
+        paragraph.clear();
+    }
+}
+
+fn flush_list(html: &mut String, list: &mut Vec<String>, tag: &str) {
+    if list.is_empty() {
+        return;
+    }
+    html.push_str(&format!("<{}>\n", tag));
+    for entry in list.drain(..) {
+        html.push_str(&format!("<li>{}</li>\n", format_inline(&entry)));
+    }
+    html.push_str(&format!("</{}>\n", tag));
+}
+
+///  Split a `## Heading` line into its level and text.
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.len() - line.trim_start_matches('#').len();
+    let text = line[level..].strip_prefix(' ')?;
+    (level > 0).then_some((level, text.trim()))
+}
+
+///  Split a `- item`, `* item` or `1. item` line into its list tag and text.
+fn list_marker(line: &str) -> Option<(&'static str, &str)> {
+    if let Some(text) = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")) {
+        return Some(("ul", text));
+    }
+    let digits = line.len() - line.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let text = line[digits..].strip_prefix(". ").or_else(|| line[digits..].strip_prefix(") "))?;
+    (digits > 0).then_some(("ol", text))
+}
+
+///  Render inline Markdown: `code` spans, **bold** and [text](url) links,
+///  whose text may itself contain code. Everything else is escaped.
+fn format_inline(text: &str) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(pos) = rest.find(['`', '[']) {
+        out.push_str(&format_bold(&rest[..pos]));
+        let tail = &rest[pos..];
+        if let Some(code) = tail.strip_prefix('`') {
+            match code.find('`') {
+                Some(end) => {
+                    out.push_str(&format!("<code>{}</code>", escape(&code[..end])));
+                    rest = &code[end + 1..];
+                }
+                //  An unclosed backtick is literal
+                None => {
+                    out.push_str(&format_bold(tail));
+                    rest = "";
+                }
+            }
+        } else if let Some((label, url, after)) = split_link(tail) {
+            out.push_str(&format!("<a href=\"{}\">{}</a>", escape(url), format_inline(label)));
+            rest = after;
+        } else {
+            out.push('[');
+            rest = &tail[1..];
+        }
+    }
+    out.push_str(&format_bold(rest));
+    out
+}
+
+///  Split `[label](url)rest` into its parts.
+fn split_link(text: &str) -> Option<(&str, &str, &str)> {
+    let (label, after) = text.strip_prefix('[')?.split_once("](")?;
+    let (url, rest) = after.split_once(')')?;
+    if label.contains('[') || url.contains(char::is_whitespace) {
+        return None;
+    }
+    Some((label, url, rest))
+}
+
+///  Escape text, turning matched `**` pairs into `<strong>`.
+fn format_bold(text: &str) -> String {
+    let parts: Vec<&str> = text.split("**").collect();
+    let closed = if parts.len() % 2 == 1 { parts.len() } else { parts.len() - 1 };
+    let mut out = String::new();
+    for (i, part) in parts.iter().enumerate() {
+        if i > 0 {
+            out.push_str(match (i % 2, i < closed) {
+                (1, true) => "<strong>",
+                (1, false) => "**",
+                _ => "</strong>",
+            });
+        }
+        out.push_str(&escape(part));
+    }
+    out
+}
+
+///  Render the contract clauses, with calls of crate spec functions linked,
+///  and the items whose contracts use this one.
+fn format_contract(html: &mut String, item: &RtDocItem) {
+    let clauses: Vec<_> = item.contract.clauses().into_iter().filter(|(_, c)| !c.is_empty()).collect();
+    if !clauses.is_empty() {
+        html.push_str("<dl class=\"contract\">\n");
+        for (keyword, clauses) in clauses {
+            html.push_str(&format!("<dt>{}</dt>\n", keyword));
+            for clause in clauses {
+                let code = highlight_with_links(clause, &item.contract_refs, true);
+                html.push_str(&format!("<dd><code>{}</code></dd>\n", code));
+            }
+        }
+        html.push_str("</dl>\n");
+    }
+
+    if !item.used_in.is_empty() {
+        let users: Vec<String> = item.used_in.iter().map(|user| xref_link(user, &user.path)).collect();
+        html.push_str(&format!("<p class=\"used-in\">Used in contracts of {}</p>\n", users.join(", ")));
+    }
+}
+
+///  Render the abstract models of a type: the `View`/`DeepView` type `V`
+///  and the body of its `view` function.
+fn format_models(html: &mut String, item: &RtDocItem) {
+    for model in &item.models {
+        let what = if model.trait_name == "DeepView" { "self.deep_view()" } else { "self@" };
+        html.push_str("<div class=\"model\">\n");
+        html.push_str(&format!("<h5>Abstract model <code>{}</code></h5>\n", highlight(&model.header)));
+        if let Some(ref ty) = model.model_type {
+            html.push_str(&format!(
+                "<p><code>{}</code> is a <code>{}</code></p>\n",
+                escape(what),
+                highlight_with_links(ty, &model.type_refs, false),
+            ));
+        }
+        match model.view_body {
+            Some(ref body) => {
+                html.push_str(&format!("<pre class=\"sig\"><code>{}</code></pre>\n", highlight(body)));
+            }
+            None => html.push_str("<p><em>The view function is closed.</em></p>\n"),
+        }
+        html.push_str("</div>\n");
+    }
+}
+
+///  Render the invariants of a type, each with its predicate's body when open.
+fn format_invariants(html: &mut String, item: &RtDocItem) {
+    if item.invariants.is_empty() {
+        return;
+    }
+    html.push_str("<div class=\"model\">\n<h5>Invariant</h5>\n<ul>\n");
+    for invariant in &item.invariants {
+        html.push_str(&format!(
+            "<li><code>{}</code> — <em>{}</em>\n",
+            highlight(&invariant.signature),
+            invariant.kind.as_str(),
+        ));
+        if let Some(ref doc) = invariant.doc_comment {
+            html.push_str(&format!("<div class=\"doc\">{}</div>\n", format_doc(doc, 5)));
+        }
+        if let Some(ref body) = invariant.body {
+            html.push_str(&format!("<pre class=\"sig\"><code>{}</code></pre>\n", highlight(body)));
+        }
+        html.push_str("</li>\n");
+    }
+    html.push_str("</ul>\n</div>\n");
+}
+
+///  Render the members of a broadcast group and the groups a lemma or group
+///  belongs to. Members outside the crate are shown without a link.
+fn format_broadcast(html: &mut String, item: &RtDocItem) {
+    if !item.group_members.is_empty() {
+        html.push_str("<h5>Members</h5>\n<ul>\n");
+        for member in &item.group_members {
+            html.push_str(&format!("<li>{}</li>\n", xref_link(member, &member.name)));
+        }
+        html.push_str("</ul>\n");
+    }
+
+    if !item.in_groups.is_empty() {
+        let groups: Vec<String> = item.in_groups.iter().map(|group| xref_link(group, &group.path)).collect();
+        html.push_str(&format!("<p class=\"used-in\">In broadcast groups {}</p>\n", groups.join(", ")));
+    }
+}
+
+///  A reference as a link labelled `label` in code, or plain code without a URL.
+fn xref_link(xref: &RtXref, label: &str) -> String {
+    match xref.url.as_str() {
+        "" => format!("<code>{}</code>", escape(label)),
+        url => format!("<a href=\"{}\"><code>{}</code></a>", escape(url), escape(label)),
+    }
+}
+
+fn format_type_members(html: &mut String, item: &RtDocItem) {
+    if !item.derives.is_empty() {
+        let derives: Vec<String> = item.derives.iter().map(|d| format!("<code>{}</code>", escape(d))).collect();
+        html.push_str(&format!("<p>Derives: {}</p>\n", derives.join(", ")));
+    }
+
+    if !item.fields.is_empty() {
+        html.push_str("<h5>Fields</h5>\n<ul>\n");
+        for field in &item.fields {
+            let vis = field.visibility.as_string();
+            let sep = if vis.is_empty() { "" } else { " " };
+            let code = format!("{}{}{}: {}", vis, sep, field.name, field.ty);
+            format_member(html, &code, &field.doc_comment);
+        }
+        html.push_str("</ul>\n");
+    }
+
+    if !item.variants.is_empty() {
+        html.push_str("<h5>Variants</h5>\n<ul>\n");
+        for variant in &item.variants {
+            format_member(html, &variant.signature, &variant.doc_comment);
+        }
+        html.push_str("</ul>\n");
+    }
+}
+
+///  Render a field, variant or associated type as a list entry.
+fn format_member(html: &mut String, code: &str, doc: &Option<String>) {
+    html.push_str(&format!("<li><code>{}</code>", highlight(code)));
+    match doc {
+        Some(doc) if !doc.contains('\n') => html.push_str(&format!(" — {}", format_inline(doc))),
+        Some(doc) => html.push_str(&format!("<div class=\"doc\">{}</div>", format_doc(doc, 5))),
+        None => {}
+    }
+    html.push_str("</li>\n");
+}
+
+fn format_trait_members(html: &mut String, item: &RtDocItem, state: &mut PageState) {
+    let assoc_types: Vec<&RtDocItem> = item
+        .members
+        .iter()
+        .filter(|m| m.item_kind == RtItemKind::AssocType)
+        .collect();
+    if !assoc_types.is_empty() {
+        html.push_str("<h5>Associated types</h5>\n<ul>\n");
+        for assoc in assoc_types {
+            format_member(html, &assoc.signature, &assoc.doc_comment);
+        }
+        html.push_str("</ul>\n");
+    }
+
+    let (required, provided) = item.trait_methods();
+    for (label, methods) in [("Required methods", required), ("Provided methods", provided)] {
+        if methods.is_empty() {
+            continue;
+        }
+        html.push_str(&format!("<h5>{}</h5>\n", label));
+        for method in methods {
+            format_item(html, method, state);
+        }
+    }
+
+    if !item.impls.is_empty() {
+        html.push_str("<h5>Implementations</h5>\n<ul>\n");
+        for imp in &item.impls {
+            html.push_str(&format!(
+                "<li><code>{}</code> <span class=\"src\">{}</span></li>\n",
+                highlight(&imp.signature),
+                source_link(&imp.file_path, imp.line_number, state.src_prefix),
+            ));
+        }
+        html.push_str("</ul>\n");
+    }
+}
+
+///  Syntax-highlight a code fragment: keywords, capitalized type names,
+///  string literals and numbers get `<span>` classes; everything is escaped.
+pub fn highlight(code: &str) -> String {
+    highlight_with_links(code, &[], false)
+}
+
+///  Like `highlight`, but names of the referenced items link to them; with
+///  `calls_only`, only where they are called.
+fn highlight_with_links(code: &str, refs: &[RtXref], calls_only: bool) -> String {
+    let chars: Vec<char> = code.chars().collect();
+    let mut out = String::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let word: String = chars[start..i].iter().collect();
+            let next = chars[i..].iter().position(|c| *c != ' ').map(|offset| i + offset);
+            let is_call = next.is_some_and(|j| chars[j] == '(' || chars[j..].starts_with(&[':', ':', '<']));
+            if let Some(target) = refs.iter().find(|r| (is_call || !calls_only) && r.name == word) {
+                out.push_str(&format!("<a href=\"{}\">{}</a>", escape(&target.url), escape(&word)));
+            } else if KEYWORDS.contains(&word.as_str()) {
+                out.push_str(&format!("<span class=\"kw\">{}</span>", word));
+            } else if word.starts_with(char::is_uppercase) {
+                out.push_str(&format!("<span class=\"ty\">{}</span>", escape(&word)));
+            } else {
+                out.push_str(&escape(&word));
+            }
+        } else if c.is_ascii_digit() {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let number: String = chars[start..i].iter().collect();
+            out.push_str(&format!("<span class=\"nu\">{}</span>", escape(&number)));
+        } else if c == '"' {
+            let start = i;
+            i += 1;
+            while i < chars.len() && chars[i] != '"' {
+                i += if chars[i] == '\\' { 2 } else { 1 };
+            }
+            i = (i + 1).min(chars.len());
+            let literal: String = chars[start..i].iter().collect();
+            out.push_str(&format!("<span class=\"st\">{}</span>", escape(&literal)));
+        } else {
+            out.push_str(&escape(&c.to_string()));
+            i += 1;
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paragraphs_are_joined_and_split_on_blank_lines() {
+        assert_eq!(format_doc("First line\nsecond line.\n\nNext.", 2), "<p>First line second line.</p>\n<p>Next.</p>\n");
+    }
+
+    #[test]
+    fn inline_code_bold_and_links() {
+        assert_eq!(format_inline("a `x < y` b"), "a <code>x &lt; y</code> b");
+        assert_eq!(format_inline("**bold** & **open"), "<strong>bold</strong> &amp; **open");
+        assert_eq!(format_inline("see [`Vec`](crate.a.html#fn.f)"), "see <a href=\"crate.a.html#fn.f\"><code>Vec</code></a>");
+        assert_eq!(format_inline("unclosed `tick and [not a link]"), "unclosed `tick and [not a link]");
+    }
+
+    #[test]
+    fn fenced_code_is_highlighted_unless_tagged_otherwise() {
+        assert_eq!(format_doc("```\nfn f\n```", 2), "<pre><code><span class=\"kw\">fn</span> f</code></pre>\n");
+        assert_eq!(format_doc("~~~text\nlet <x>\n~~~", 2), "<pre><code>let &lt;x&gt;</code></pre>\n");
+    }
+
+    #[test]
+    fn search_entry_has_first_sentence_and_contract() {
+        let lemma = RtDocItem {
+            name: "lemma_le_trans".to_string(),
+            module_path: "crate::seq".to_string(),
+            kind: RtFnKind::Proof,
+            doc_comment: Some("Transitivity of `le`. Used by\nthe sort proofs.\n\nMore.".to_string()),
+            contract: RtContract {
+                requires: vec!["le(a, b)".to_string(), "le(b, c)".to_string()],
+                ensures: vec!["le(a, c)".to_string()],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let entry = search_entry(&lemma, "crate.seq.html#fn.lemma_le_trans");
+        assert_eq!(
+            entry,
+            Json::object(vec![
+                ("name", Json::string("lemma_le_trans")),
+                ("path", Json::string("crate::seq")),
+                ("kind", Json::string("fn")),
+                ("fn_kind", Json::string("proof")),
+                ("open", Json::Bool(false)),
+                ("doc", Json::string("Transitivity of `le`.")),
+                ("contract", Json::string("requires le(a, b), le(b, c); ensures le(a, c)")),
+                ("url", Json::string("crate.seq.html#fn.lemma_le_trans")),
+            ])
+        );
+        assert_eq!(first_sentence("No period\nhere.\n\nNext"), "No period here.");
+    }
+
+    #[test]
+    fn lists_and_headings_keep_their_structure() {
+        let doc = "# Usage\n- one\n  continued\n- two\n1. first\n2) second\n\n#not a heading";
+        assert_eq!(
+            format_doc(doc, 2),
+            "<h3>Usage</h3>\n\
+             <ul>\n<li>one continued</li>\n<li>two</li>\n</ul>\n\
+             <ol>\n<li>first</li>\n<li>second</li>\n</ol>\n\
+             <p>#not a heading</p>\n"
+        );
+        assert_eq!(format_doc("#### Deep", 4), "<h6>Deep</h6>\n");
+    }
+
+    #[test]
+    fn contract_links_only_at_calls() {
+        let refs = vec![RtXref { name: "len".to_string(), path: "a::len".to_string(), url: "crate.a.html#fn.len".to_string() }];
+        let link = "<a href=\"crate.a.html#fn.len\">len</a>";
+        assert_eq!(highlight_with_links("len(s)", &refs, true), format!("{}(s)", link));
+        assert_eq!(highlight_with_links("len::<T>(s)", &refs, true), format!("{}::&lt;<span class=\"ty\">T</span>&gt;(s)", link));
+        assert_eq!(highlight_with_links("len: nat", &refs, true), "len: nat");
+        assert_eq!(highlight_with_links("len::MAX", &refs, true), "len::<span class=\"ty\">MAX</span>");
+        assert_eq!(highlight_with_links("len: nat", &refs, false), format!("{}: nat", link));
+    }
+}
@@ -0,0 +1,341 @@
+use crate::doc_item::*;
+
+///  Version of the JSON schema emitted by `format_doc_output`.
+///  Bump on any change that removes or renames a field; additions keep it.
+pub const SCHEMA_VERSION: u64 = 1;
+
+///  Minimal JSON value used to build output documents.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(u64),
+    String(String),
+    Array(Vec<Json>),
+    ///  Fields keep insertion order so output is stable.
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    ///  Build an object from `(key, value)` pairs.
+    pub fn object(fields: Vec<(&str, Json)>) -> Json {
+        Json::Object(fields.into_iter().map(|(k, v)| (k.to_string(), v)).collect())
+    }
+
+    pub fn string(s: &str) -> Json {
+        Json::String(s.to_string())
+    }
+
+    pub fn optional_string(s: &Option<String>) -> Json {
+        s.as_deref().map_or(Json::Null, Json::string)
+    }
+
+    pub fn strings(items: &[String]) -> Json {
+        Json::Array(items.iter().map(|s| Json::string(s)).collect())
+    }
+
+    ///  Serialize with two-space indentation and a trailing newline.
+    pub fn to_pretty_string(&self) -> String {
+        let mut out = String::new();
+        self.write_pretty(&mut out, 0);
+        out.push('\n');
+        out
+    }
+
+    fn write_pretty(&self, out: &mut String, indent: usize) {
+        match self {
+            Json::Null => out.push_str("null"),
+            Json::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+            Json::Number(n) => out.push_str(&n.to_string()),
+            Json::String(s) => write_escaped(out, s),
+            Json::Array(items) if items.is_empty() => out.push_str("[]"),
+            Json::Object(fields) if fields.is_empty() => out.push_str("{}"),
+            Json::Array(items) => {
+                out.push_str("[\n");
+                for (i, item) in items.iter().enumerate() {
+                    push_indent(out, indent + 1);
+                    item.write_pretty(out, indent + 1);
+                    out.push_str(if i + 1 < items.len() { ",\n" } else { "\n" });
+                }
+                push_indent(out, indent);
+                out.push(']');
+            }
+            Json::Object(fields) => {
+                out.push_str("{\n");
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    push_indent(out, indent + 1);
+                    write_escaped(out, key);
+                    out.push_str(": ");
+                    value.write_pretty(out, indent + 1);
+                    out.push_str(if i + 1 < fields.len() { ",\n" } else { "\n" });
+                }
+                push_indent(out, indent);
+                out.push('}');
+            }
+        }
+    }
+}
+
+fn push_indent(out: &mut String, indent: usize) {
+    for _ in 0..indent {
+        out.push_str("  ");
+    }
+}
+
+///  Write a JSON string literal, escaping quotes, backslashes and control characters.
+fn write_escaped(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+///  Generate a JSON document from the documentation output.
+pub fn format_doc_output(output: &RtDocOutput) -> String {
+    doc_output_to_json(output).to_pretty_string()
+}
+
+///  Convert the documentation output to the versioned JSON schema:
+///  `{ schema_version, generator, modules: [{ path, doc, items: [...] }] }`.
+pub fn doc_output_to_json(output: &RtDocOutput) -> Json {
+    let modules = output
+        .modules
+        .iter()
+        .map(|module| {
+            Json::object(vec![
+                ("path", Json::string(&module.path)),
+                ("doc", Json::optional_string(&module.doc)),
+                ("items", Json::Array(module.items.iter().map(item_to_json).collect())),
+            ])
+        })
+        .collect();
+
+    Json::object(vec![
+        ("schema_version", Json::Number(SCHEMA_VERSION)),
+        ("generator", Json::string("verus-docgenerator")),
+        ("modules", Json::Array(modules)),
+    ])
+}
+
+///  Stable schema name for an item category.
+fn item_kind_name(kind: RtItemKind) -> &'static str {
+    match kind {
+        RtItemKind::Function => "function",
+        RtItemKind::Struct => "struct",
+        RtItemKind::Enum => "enum",
+        RtItemKind::Trait => "trait",
+        RtItemKind::AssocType => "assoc_type",
+        RtItemKind::TypeAlias => "type_alias",
+        RtItemKind::Impl => "impl",
+        RtItemKind::Module => "module",
+        RtItemKind::Use => "use",
+        RtItemKind::BroadcastGroup => "broadcast_group",
+        RtItemKind::Const => "const",
+        RtItemKind::Static => "static",
+    }
+}
+
+fn span_to_json(item: &RtDocItem) -> Json {
+    Json::object(vec![
+        ("file", Json::string(&item.file_path)),
+        ("start_line", Json::Number(item.line_number as u64)),
+        ("end_line", Json::Number(item.end_line_number as u64)),
+    ])
+}
+
+fn contract_to_json(contract: &RtContract) -> Json {
+    Json::Object(
+        contract
+            .clauses()
+            .iter()
+            .map(|(keyword, clauses)| (keyword.to_string(), Json::strings(clauses)))
+            .collect(),
+    )
+}
+
+///  Paths of referenced items, relative to the crate root.
+fn xref_paths(refs: &[RtXref]) -> Json {
+    Json::Array(refs.iter().map(|r| Json::string(&r.path)).collect())
+}
+
+///  Trusted sites as `{ kind, line, text }` objects.
+fn trust_to_json(sites: &[RtTrustSite]) -> Json {
+    Json::Array(
+        sites
+            .iter()
+            .map(|site| {
+                Json::object(vec![
+                    ("kind", Json::string(site.kind.as_str())),
+                    ("line", Json::Number(site.line_number as u64)),
+                    ("text", Json::string(&site.text)),
+                ])
+            })
+            .collect(),
+    )
+}
+
+///  Abstract models as `{ trait, header, model_type, type_refs, view_body, line }` objects.
+fn models_to_json(models: &[RtAbstractModel]) -> Json {
+    Json::Array(
+        models
+            .iter()
+            .map(|model| {
+                Json::object(vec![
+                    ("trait", Json::string(&model.trait_name)),
+                    ("header", Json::string(&model.header)),
+                    ("model_type", Json::optional_string(&model.model_type)),
+                    ("type_refs", xref_paths(&model.type_refs)),
+                    ("view_body", Json::optional_string(&model.view_body)),
+                    ("line", Json::Number(model.line_number as u64)),
+                ])
+            })
+            .collect(),
+    )
+}
+
+///  Invariants as `{ kind, name, signature, body, doc_comment, line }` objects.
+fn invariants_to_json(invariants: &[RtInvariant]) -> Json {
+    Json::Array(
+        invariants
+            .iter()
+            .map(|invariant| {
+                Json::object(vec![
+                    ("kind", Json::string(invariant_kind_name(invariant.kind))),
+                    ("name", Json::string(&invariant.name)),
+                    ("signature", Json::string(&invariant.signature)),
+                    ("body", Json::optional_string(&invariant.body)),
+                    ("doc_comment", Json::optional_string(&invariant.doc_comment)),
+                    ("line", Json::Number(invariant.line_number as u64)),
+                ])
+            })
+            .collect(),
+    )
+}
+
+///  Stable schema name for an invariant kind.
+fn invariant_kind_name(kind: RtInvariantKind) -> &'static str {
+    match kind {
+        RtInvariantKind::TypeInvariant => "type_invariant",
+        RtInvariantKind::WellFormed => "well_formed",
+    }
+}
+
+///  Convert one item. `fn_kind` and `contract` are null for non-functions.
+fn item_to_json(item: &RtDocItem) -> Json {
+    let is_fn = item.item_kind == RtItemKind::Function;
+    let impl_block = item.impl_block.as_ref().map_or(Json::Null, |block| {
+        Json::object(vec![
+            ("self_ty", Json::string(&block.self_ty)),
+            ("trait", Json::optional_string(&block.trait_path)),
+            ("generics", Json::optional_string(&block.generics)),
+        ])
+    });
+
+    let fields = item
+        .fields
+        .iter()
+        .map(|field| {
+            Json::object(vec![
+                ("name", Json::string(&field.name)),
+                ("type", Json::string(&field.ty)),
+                ("visibility", Json::string(&field.visibility.as_string())),
+                ("line", Json::Number(field.line_number as u64)),
+                ("doc_comment", Json::optional_string(&field.doc_comment)),
+            ])
+        })
+        .collect();
+
+    let variants = item
+        .variants
+        .iter()
+        .map(|variant| {
+            Json::object(vec![
+                ("name", Json::string(&variant.name)),
+                ("signature", Json::string(&variant.signature)),
+                ("line", Json::Number(variant.line_number as u64)),
+                ("doc_comment", Json::optional_string(&variant.doc_comment)),
+            ])
+        })
+        .collect();
+
+    let impls = item
+        .impls
+        .iter()
+        .map(|imp| {
+            Json::object(vec![
+                ("header", Json::string(&imp.signature)),
+                ("module_path", Json::string(&imp.module_path)),
+                ("span", span_to_json(imp)),
+            ])
+        })
+        .collect();
+
+    Json::object(vec![
+        ("name", Json::string(&item.name)),
+        ("item_kind", Json::string(item_kind_name(item.item_kind))),
+        ("fn_kind", if is_fn { Json::string(item.kind.as_str()) } else { Json::Null }),
+        ("visibility", Json::string(&item.visibility.as_string())),
+        ("is_open", Json::Bool(item.is_open)),
+        ("is_broadcast", Json::Bool(item.is_broadcast)),
+        ("has_body", Json::Bool(item.has_body)),
+        ("module_path", Json::string(&item.module_path)),
+        ("span", span_to_json(item)),
+        ("signature", Json::string(&item.signature)),
+        ("value", Json::optional_string(&item.value)),
+        ("spec_body", Json::optional_string(&item.spec_body)),
+        ("aliased_type", Json::optional_string(&item.aliased_type)),
+        ("type_refs", xref_paths(&item.type_refs)),
+        ("doc_comment", Json::optional_string(&item.doc_comment)),
+        ("attributes", Json::strings(&item.attributes)),
+        ("contract", if is_fn { contract_to_json(&item.contract) } else { Json::Null }),
+        ("derives", Json::strings(&item.derives)),
+        ("fields", Json::Array(fields)),
+        ("variants", Json::Array(variants)),
+        ("models", models_to_json(&item.models)),
+        ("invariants", invariants_to_json(&item.invariants)),
+        ("invariant_kind", item.invariant_kind.map_or(Json::Null, |k| Json::string(invariant_kind_name(k)))),
+        ("members", Json::Array(item.members.iter().map(item_to_json).collect())),
+        ("impls", Json::Array(impls)),
+        ("impl", impl_block),
+        ("reexport_of", Json::optional_string(&item.reexport_of)),
+        ("contract_refs", xref_paths(&item.contract_refs)),
+        ("used_in", xref_paths(&item.used_in)),
+        ("trust", trust_to_json(&item.trust)),
+        ("group_members", Json::Array(item.group_members.iter().map(|m| Json::string(&m.path)).collect())),
+        ("in_groups", xref_paths(&item.in_groups)),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strings_are_escaped() {
+        let json = Json::string("a \"quoted\" \\path\\\nline\ttab\r\u{1}é");
+        assert_eq!(json.to_pretty_string(), "\"a \\\"quoted\\\" \\\\path\\\\\\nline\\ttab\\r\\u0001é\"\n");
+    }
+
+    #[test]
+    fn keys_are_escaped_and_nesting_is_indented() {
+        let json = Json::object(vec![
+            ("k\"ey", Json::strings(&["x".to_string()])),
+            ("empty", Json::Array(Vec::new())),
+            ("none", Json::optional_string(&None)),
+            ("n", Json::Number(3)),
+        ]);
+        assert_eq!(
+            json.to_pretty_string(),
+            "{\n  \"k\\\"ey\": [\n    \"x\"\n  ],\n  \"empty\": [],\n  \"none\": null,\n  \"n\": 3\n}\n"
+        );
+    }
+}
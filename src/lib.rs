@@ -1,5 +1,16 @@
+pub mod contract;
+pub mod coverage;
 pub mod doc_item;
 pub mod extraction;
+pub mod graph;
 pub mod grouping;
+pub mod html;
+pub mod json;
+pub mod links;
 pub mod markdown;
+pub mod mdbook;
+pub mod module_tree;
+pub mod scope;
 pub mod tree_sitter_wrapper;
+pub mod trust;
+pub mod visibility;
@@ -0,0 +1,784 @@
+use crate::doc_item::*;
+use crate::html;
+use crate::mdbook;
+
+use std::collections::HashMap;
+
+///  How links to documented items are written for an output format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkStyle {
+    ///  One Markdown file: `#crate.a.fn.name`.
+    Markdown,
+    ///  One Markdown file per module: `crate.a.md#crate.a.fn.name`.
+    MdBook,
+    ///  One HTML page per module: `crate.a.html#fn.name`.
+    Html,
+}
+
+impl LinkStyle {
+    ///  URL of an anchor on a module page, or of the page itself.
+    pub fn url(self, page: &str, anchor: Option<&str>) -> String {
+        match (self, anchor) {
+            (LinkStyle::Markdown, Some(anchor)) => format!("#{}", markdown_id(page, anchor)),
+            (LinkStyle::Markdown, None) => format!("#{}", page_stem(page)),
+            (LinkStyle::MdBook, Some(anchor)) => {
+                format!("{}#{}", mdbook::module_file_name(page), markdown_id(page, anchor))
+            }
+            (LinkStyle::MdBook, None) => mdbook::module_file_name(page),
+            (LinkStyle::Html, Some(anchor)) => format!("{}#{}", html::module_file_name(page), anchor),
+            (LinkStyle::Html, None) => html::module_file_name(page),
+        }
+    }
+}
+
+///  `crate::a::b` as used in file names and ids: `crate.a.b`.
+pub fn page_stem(page: &str) -> String {
+    page.replace("::", ".")
+}
+
+///  Id of an item in Markdown output. Unlike HTML anchors these include the
+///  module, since a single Markdown file holds every module.
+pub fn markdown_id(page: &str, anchor: &str) -> String {
+    format!("{}.{}", page_stem(page), anchor)
+}
+
+///  Where a documented path is rendered.
+struct Target {
+    ///  Full path of the documented item or module.
+    path: String,
+    page: String,
+    ///  None for links to a module page itself.
+    anchor: Option<String>,
+    is_spec_fn: bool,
+    ///  A struct, enum, trait or type alias, which type references may link to.
+    is_type: bool,
+}
+
+///  Documented items by full path (`crate::a::Type::method`), plus full
+///  paths by simple name for links that give only a name.
+pub struct LinkIndex {
+    targets: HashMap<String, Target>,
+    by_name: HashMap<String, Vec<String>>,
+}
+
+impl LinkIndex {
+    pub fn new(output: &RtDocOutput) -> Self {
+        let mut index = LinkIndex { targets: HashMap::new(), by_name: HashMap::new() };
+        for module in &output.modules {
+            index.insert(module.path.clone(), &module.path, None);
+            for item in &module.items {
+                let path = format!("{}::{}", item.module_path, item.name);
+                index.insert(path, &module.path, Some(item));
+                for method in item.members.iter().filter(|m| !m.anchor.is_empty()) {
+                    let path = format!("{}::{}", method.module_path, method.name);
+                    index.insert(path, &module.path, Some(method));
+                }
+            }
+        }
+        index
+    }
+
+    ///  Record a path to `item`, or to the module page itself when None; the
+    ///  first item rendered at a path wins.
+    fn insert(&mut self, path: String, page: &str, item: Option<&RtDocItem>) {
+        if self.targets.contains_key(&path) {
+            return;
+        }
+        let name = path.rsplit("::").next().unwrap_or(&path).to_string();
+        self.by_name.entry(name).or_default().push(path.clone());
+        let target = Target {
+            path: path.clone(),
+            page: page.to_string(),
+            anchor: item.map(|i| i.anchor.clone()),
+            is_spec_fn: item.is_some_and(is_spec_fn),
+            is_type: item.is_some_and(|i| {
+                i.item_kind.is_type() || matches!(i.item_kind, RtItemKind::Trait | RtItemKind::TypeAlias)
+            }),
+        };
+        self.targets.insert(path, target);
+    }
+
+    ///  Resolve a link path written in `context`: `Self::` is the enclosing
+    ///  type or trait, `crate::`, `self::` and `super::` are followed as in
+    ///  Rust, other paths are tried in the enclosing scopes from the innermost
+    ///  outwards, and a bare name falls back to a crate-wide unique match.
+    fn resolve(&self, path: &str, context: &LinkContext) -> Result<&Target, String> {
+        let not_found = || format!("unresolved link `{}`", path);
+
+        if let Some(rest) = path.strip_prefix("Self::") {
+            let self_path = context.self_path.as_ref().ok_or_else(|| format!("`Self` outside a type in `{}`", path))?;
+            return self.lookup(&format!("{}::{}", self_path, rest)).ok_or_else(not_found);
+        }
+
+        let mut segments = path.split("::");
+        match segments.next() {
+            Some("crate") => return self.lookup(path).ok_or_else(not_found),
+            Some("self") | Some("super") => {
+                let mut scope = context.module.clone();
+                for segment in path.split("::") {
+                    match segment {
+                        "self" => {}
+                        "super" => scope = scope.rsplit_once("::").ok_or_else(not_found)?.0.to_string(),
+                        name => scope = format!("{}::{}", scope, name),
+                    }
+                }
+                return self.lookup(&scope).ok_or_else(not_found);
+            }
+            _ => {}
+        }
+
+        let mut scope = context.scope.as_str();
+        loop {
+            if let Some(target) = self.lookup(&format!("{}::{}", scope, path)) {
+                return Ok(target);
+            }
+            match scope.rsplit_once("::") {
+                Some((parent, _)) => scope = parent,
+                None => break,
+            }
+        }
+
+        if path.contains("::") {
+            return Err(not_found());
+        }
+        match self.by_name.get(path).map(Vec::as_slice) {
+            Some([only]) => self.lookup(only).ok_or_else(not_found),
+            Some(candidates) if candidates.len() > 1 => {
+                Err(format!("ambiguous link `{}` (matches {})", path, candidates.join(", ")))
+            }
+            _ => Err(not_found()),
+        }
+    }
+
+    ///  Resolve a path written in `item` (a member of trait `owner`, if set)
+    ///  to the full path of a documented item, without reporting failures.
+    pub fn resolve_in(&self, path: &str, item: &RtDocItem, owner: Option<&str>) -> Option<&str> {
+        let context = item_context(item, owner);
+        self.resolve(path, &context).ok().map(|target| target.path.as_str())
+    }
+
+    fn lookup(&self, path: &str) -> Option<&Target> {
+        self.targets.get(path)
+    }
+}
+
+///  Where a doc comment appears, for resolving relative links and reporting.
+struct LinkContext {
+    ///  The module the doc is written in.
+    module: String,
+    ///  Innermost scope for relative paths: the enclosing type or trait for
+    ///  its methods, otherwise the module.
+    scope: String,
+    ///  Full path of the type or trait `Self` refers to, if any.
+    self_path: Option<String>,
+    ///  `file:line` (or module path) used in warnings.
+    location: String,
+}
+
+///  Rewrite rustdoc-style intra-doc links (``[`name`]``, `[name]`,
+///  `[text](path)` and `[text][path]`) in every doc comment of the output
+///  into ordinary Markdown links in `style`. Links that cannot be resolved
+///  are left as written and reported as warnings.
+pub fn resolve_doc_links(output: &mut RtDocOutput, style: LinkStyle) {
+    let index = LinkIndex::new(output);
+
+    for module in &mut output.modules {
+        let context = LinkContext {
+            module: module.path.clone(),
+            scope: module.path.clone(),
+            self_path: None,
+            location: module.path.clone(),
+        };
+        rewrite_doc(&mut module.doc, &index, &context, style);
+
+        for item in &mut module.items {
+            rewrite_item_docs(item, None, &index, style);
+        }
+    }
+}
+
+///  Rewrite the docs of an item, its fields and variants, and its trait
+///  members. `owner` is the full path of the trait the item belongs to.
+fn rewrite_item_docs(item: &mut RtDocItem, owner: Option<&str>, index: &LinkIndex, style: LinkStyle) {
+    let context = item_context(item, owner);
+    rewrite_doc(&mut item.doc_comment, index, &context, style);
+    for field in &mut item.fields {
+        rewrite_doc(&mut field.doc_comment, index, &context, style);
+    }
+    for variant in &mut item.variants {
+        rewrite_doc(&mut variant.doc_comment, index, &context, style);
+    }
+
+    let owner = trait_path(item);
+    for member in &mut item.members {
+        rewrite_item_docs(member, owner.as_deref(), index, style);
+    }
+}
+
+///  Full path of a trait, which its members use as `Self`.
+pub fn trait_path(item: &RtDocItem) -> Option<String> {
+    (item.item_kind == RtItemKind::Trait).then(|| format!("{}::{}", item.module_path, item.name))
+}
+
+fn is_spec_fn(item: &RtDocItem) -> bool {
+    item.item_kind == RtItemKind::Function && item.kind == RtFnKind::Spec
+}
+
+///  The context paths in an item's doc and contract are resolved in. `owner`
+///  is the full path of the trait the item belongs to, if any.
+fn item_context(item: &RtDocItem, owner: Option<&str>) -> LinkContext {
+    let own_path = format!("{}::{}", item.module_path, item.name);
+    let self_path = if item.item_kind.is_type() || item.item_kind == RtItemKind::Trait {
+        Some(own_path.clone())
+    } else if item.impl_block.is_some() {
+        Some(item.module_path.clone())
+    } else {
+        owner.map(str::to_string)
+    };
+    //  Methods live in their type's pseudo-module; `module` is the real one
+    let module = match self_path {
+        Some(ref path) if *path == item.module_path => {
+            path.rsplit_once("::").map_or(path.clone(), |(parent, _)| parent.to_string())
+        }
+        _ => item.module_path.clone(),
+    };
+    LinkContext {
+        module,
+        scope: self_path.clone().unwrap_or_else(|| item.module_path.clone()),
+        self_path,
+        location: format!("{}:{}", item.file_path, item.line_number),
+    }
+}
+
+fn rewrite_doc(doc: &mut Option<String>, index: &LinkIndex, context: &LinkContext, style: LinkStyle) {
+    let Some(text) = doc.as_deref() else {
+        return;
+    };
+
+    let mut lines = Vec::new();
+    let mut in_fence = false;
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+        }
+        if in_fence {
+            lines.push(line.to_string());
+        } else {
+            lines.push(rewrite_line(line, index, context, style));
+        }
+    }
+    *doc = Some(lines.join("\n"));
+}
+
+///  Rewrite the intra-doc links of one line, skipping inline code spans.
+fn rewrite_line(line: &str, index: &LinkIndex, context: &LinkContext, style: LinkStyle) -> String {
+    let bytes = line.as_bytes();
+    let mut out = String::new();
+    let mut copied = 0;
+    let mut in_code = false;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'`' => in_code = !in_code,
+            b'[' if !in_code => {
+                if let Some(link) = parse_link(line, i) {
+                    //  `a[i]` is indexing, not a link
+                    let after_word = i > 0 && (bytes[i - 1].is_ascii_alphanumeric() || b"_])".contains(&bytes[i - 1]));
+                    let explicit = link.form != LinkForm::Shortcut;
+                    if let Some(path) = link_path(link.target).filter(|_| explicit || !after_word) {
+                        match index.resolve(&path, context) {
+                            Ok(target) => {
+                                out.push_str(&line[copied..i]);
+                                let url = style.url(&target.page, target.anchor.as_deref());
+                                out.push_str(&format!("[{}]({})", link.label, url));
+                                copied = link.end;
+                            }
+                            //  Bare `[word]` is often just bracketed text, unlike `[seq_len]`
+                            Err(_) if link.form == LinkForm::Shortcut && !is_identifier_like(link.target) => {}
+                            Err(reason) => eprintln!("Warning: {} in doc of {}", reason, context.location),
+                        }
+                    }
+                    i = link.end;
+                    continue;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+
+    out.push_str(&line[copied..]);
+    out
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LinkForm {
+    ///  `[target]`
+    Shortcut,
+    ///  `[label](target)`
+    Inline,
+    ///  `[label][target]`
+    Reference,
+}
+
+struct ParsedLink<'a> {
+    label: &'a str,
+    target: &'a str,
+    form: LinkForm,
+    ///  Byte offset just past the link.
+    end: usize,
+}
+
+///  Parse a Markdown link starting at the `[` at byte `start`.
+fn parse_link(line: &str, start: usize) -> Option<ParsedLink<'_>> {
+    let label_end = start + 1 + line[start + 1..].find(']')?;
+    let label = &line[start + 1..label_end];
+    if label.is_empty() || label.contains('[') {
+        return None;
+    }
+
+    let rest = &line[label_end + 1..];
+    let (form, close) = if rest.starts_with('(') {
+        (LinkForm::Inline, ')')
+    } else if rest.starts_with('[') {
+        (LinkForm::Reference, ']')
+    } else {
+        return Some(ParsedLink { label, target: label, form: LinkForm::Shortcut, end: label_end + 1 });
+    };
+    let target_len = rest[1..].find(close)?;
+    let target = &rest[1..1 + target_len];
+    let end = label_end + 1 + target_len + 2;
+    let target = if target.is_empty() { label } else { target };
+    Some(ParsedLink { label, target, form, end })
+}
+
+///  Whether a shortcut link target reads as code rather than bracketed
+///  prose: it has backticks, a path separator or an underscore, or mixes
+///  case like `SeqChar`.
+fn is_identifier_like(target: &str) -> bool {
+    target.contains(['`', ':', '_'])
+        || (target.chars().skip(1).any(char::is_uppercase) && target.chars().any(char::is_lowercase))
+}
+
+///  The Rust path named by a link target, without backticks, a disambiguator
+///  such as `fn@`, or a trailing `()`/`!`. None if it is not a path (e.g. a URL).
+fn link_path(target: &str) -> Option<String> {
+    let mut path = target.trim().trim_matches('`');
+    if let Some((prefix, rest)) = path.split_once('@') {
+        if prefix.chars().all(|c| c.is_ascii_lowercase()) {
+            path = rest;
+        }
+    }
+    let path = path.strip_suffix("()").or_else(|| path.strip_suffix('!')).unwrap_or(path);
+
+    let is_ident = |s: &str| {
+        s.chars().next().is_some_and(|c| c.is_alphabetic() || c == '_')
+            && s.chars().all(|c| c.is_alphanumeric() || c == '_')
+    };
+    path.split("::").all(is_ident).then(|| path.to_string())
+}
+
+///  Link the spec functions called in each item's contract clauses
+///  (`contract_refs`), and list on each spec function the items whose
+///  contracts call it (`used_in`). Calls are resolved like intra-doc links;
+///  `x.f(...)` is tried as `Self::f`. Calls to anything other than a spec
+///  function of the crate, e.g. vstd, are ignored.
+pub fn resolve_contract_refs(output: &mut RtDocOutput, style: LinkStyle) {
+    let index = LinkIndex::new(output);
+    let mut users: HashMap<String, Vec<RtXref>> = HashMap::new();
+
+    for module in &mut output.modules {
+        for item in &mut module.items {
+            link_contract(item, None, &module.path, &index, style, &mut users);
+        }
+    }
+
+    for module in &mut output.modules {
+        for item in &mut module.items {
+            attach_users(item, &mut users);
+        }
+    }
+}
+
+fn link_contract(
+    item: &mut RtDocItem,
+    owner: Option<&str>,
+    page: &str,
+    index: &LinkIndex,
+    style: LinkStyle,
+    users: &mut HashMap<String, Vec<RtXref>>,
+) {
+    let context = item_context(item, owner);
+    let own_path = format!("{}::{}", item.module_path, item.name);
+    let user = RtXref {
+        name: item.name.clone(),
+        path: display_path(&own_path),
+        url: style.url(page, Some(&item.anchor)),
+    };
+
+    let mut refs: Vec<RtXref> = Vec::new();
+    for (_, clauses) in item.contract.clauses() {
+        for (name, path) in clause_calls(clauses) {
+            let Ok(target) = index.resolve(&path, &context) else {
+                continue;
+            };
+            let target_path = display_path(&target.path);
+            if !target.is_spec_fn || target.path == own_path || refs.iter().any(|r| r.path == target_path) {
+                continue;
+            }
+            refs.push(RtXref {
+                name,
+                path: target_path,
+                url: style.url(&target.page, target.anchor.as_deref()),
+            });
+            users.entry(target.path.clone()).or_default().push(user.clone());
+        }
+    }
+    item.contract_refs = refs;
+
+    let owner = trait_path(item);
+    for member in item.members.iter_mut().filter(|m| !m.anchor.is_empty()) {
+        link_contract(member, owner.as_deref(), page, index, style, users);
+    }
+}
+
+fn attach_users(item: &mut RtDocItem, users: &mut HashMap<String, Vec<RtXref>>) {
+    if let Some(list) = users.remove(&format!("{}::{}", item.module_path, item.name)) {
+        item.used_in = list;
+    }
+    for member in &mut item.members {
+        attach_users(member, users);
+    }
+}
+
+///  Resolve the members of each broadcast group to the lemmas and groups of
+///  the crate they name, and list on each of those the groups that include
+///  them (`in_groups`). Members outside the crate, e.g. vstd groups, keep
+///  their path as written and no link.
+pub fn resolve_broadcast_groups(output: &mut RtDocOutput, style: LinkStyle) {
+    let index = LinkIndex::new(output);
+    let mut groups: HashMap<String, Vec<RtXref>> = HashMap::new();
+
+    for module in &mut output.modules {
+        for item in module.items.iter_mut().filter(|i| i.item_kind == RtItemKind::BroadcastGroup) {
+            let context = item_context(item, None);
+            let group = RtXref {
+                name: item.name.clone(),
+                path: display_path(&format!("{}::{}", item.module_path, item.name)),
+                url: style.url(&module.path, Some(&item.anchor)),
+            };
+            for member in &mut item.group_members {
+                let Ok(target) = index.resolve(&member.name, &context) else {
+                    continue;
+                };
+                member.path = display_path(&target.path);
+                member.url = style.url(&target.page, target.anchor.as_deref());
+                groups.entry(target.path.clone()).or_default().push(group.clone());
+            }
+        }
+    }
+
+    for module in &mut output.modules {
+        for item in &mut module.items {
+            attach_groups(item, &mut groups);
+        }
+    }
+}
+
+fn attach_groups(item: &mut RtDocItem, groups: &mut HashMap<String, Vec<RtXref>>) {
+    if let Some(list) = groups.remove(&format!("{}::{}", item.module_path, item.name)) {
+        item.in_groups = list;
+    }
+    for member in &mut item.members {
+        attach_groups(member, groups);
+    }
+}
+
+///  Link the types of the crate named in each type alias and associated type
+///  definition (`type_refs`) and in each abstract model type. Other names,
+///  e.g. `Seq` from vstd, are ignored.
+pub fn resolve_type_refs(output: &mut RtDocOutput, style: LinkStyle) {
+    let index = LinkIndex::new(output);
+    for module in &mut output.modules {
+        for item in &mut module.items {
+            link_types(item, None, &index, style);
+        }
+    }
+}
+
+fn link_types(item: &mut RtDocItem, owner: Option<&str>, index: &LinkIndex, style: LinkStyle) {
+    let context = item_context(item, owner);
+    if let Some(ref ty) = item.aliased_type {
+        item.type_refs = type_refs(ty, &context, index, style);
+    }
+    for model in &mut item.models {
+        if let Some(ref ty) = model.model_type {
+            model.type_refs = type_refs(ty, &context, index, style);
+        }
+    }
+
+    let owner = trait_path(item);
+    for member in &mut item.members {
+        link_types(member, owner.as_deref(), index, style);
+    }
+}
+
+///  Links to the types of the crate named in `ty`, without repeats.
+fn type_refs(ty: &str, context: &LinkContext, index: &LinkIndex, style: LinkStyle) -> Vec<RtXref> {
+    let mut refs: Vec<RtXref> = Vec::new();
+    for path in type_paths(ty) {
+        let Ok(target) = index.resolve(&path, context) else {
+            continue;
+        };
+        let target_path = display_path(&target.path);
+        if !target.is_type || refs.iter().any(|r| r.path == target_path) {
+            continue;
+        }
+        refs.push(RtXref {
+            name: path.rsplit("::").next().unwrap_or(&path).to_string(),
+            path: target_path,
+            url: style.url(&target.page, target.anchor.as_deref()),
+        });
+    }
+    refs
+}
+
+///  The paths named in a type, e.g. `Seq` and `crate::a::Foo` in
+///  `Seq<crate::a::Foo>`, in order of appearance.
+fn type_paths(ty: &str) -> Vec<String> {
+    let bytes = ty.as_bytes();
+    let mut paths = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'\'' {
+            //  Skip lifetimes
+            i += 1;
+            while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+                i += 1;
+            }
+            continue;
+        }
+        if !(bytes[i].is_ascii_alphabetic() || bytes[i] == b'_') {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        loop {
+            while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+                i += 1;
+            }
+            let next_is_ident = bytes.get(i + 2).is_some_and(|b| b.is_ascii_alphabetic() || *b == b'_');
+            if bytes[i..].starts_with(b"::") && next_is_ident {
+                i += 2;
+            } else {
+                break;
+            }
+        }
+        paths.push(ty[start..i].to_string());
+    }
+    paths
+}
+
+///  A full path without the leading `crate::`, as shown to readers.
+fn display_path(path: &str) -> String {
+    path.strip_prefix("crate::").unwrap_or(path).to_string()
+}
+
+///  Calls in contract clauses as `(name, path)`: `f(..)`, `a::f(..)` and
+///  `f::<T>(..)` give the path as written, `self.f(..)` gives `Self::f`.
+///  Other method calls are skipped, since the receiver's type is unknown.
+///  `name` is the last path segment.
+fn clause_calls(clauses: &[String]) -> Vec<(String, String)> {
+    let mut calls = Vec::new();
+    for clause in clauses {
+        let bytes = clause.as_bytes();
+        let mut i = 0;
+        while i < bytes.len() {
+            let c = bytes[i];
+            if c == b'"' {
+                //  Skip string literals
+                i += 1;
+                while i < bytes.len() && bytes[i] != b'"' {
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
+                }
+                i += 1;
+                continue;
+            }
+            if !(c.is_ascii_alphabetic() || c == b'_') {
+                i += 1;
+                continue;
+            }
+
+            let start = i;
+            let is_method = start > 0 && bytes[start - 1] == b'.';
+            let mut end = i;
+            loop {
+                while end < bytes.len() && (bytes[end].is_ascii_alphanumeric() || bytes[end] == b'_') {
+                    end += 1;
+                }
+                let next_is_ident = bytes.get(end + 2).is_some_and(|b| b.is_ascii_alphabetic() || *b == b'_');
+                if !is_method && bytes[end..].starts_with(b"::") && next_is_ident {
+                    end += 2;
+                } else {
+                    break;
+                }
+            }
+            let path = &clause[start..end];
+
+            //  Optional turbofish, then the call's `(`
+            let mut j = end;
+            if bytes[j..].starts_with(b"::<") {
+                let mut depth = 0;
+                while j < bytes.len() {
+                    match bytes[j] {
+                        b'<' => depth += 1,
+                        b'>' => {
+                            depth -= 1;
+                            if depth == 0 {
+                                j += 1;
+                                break;
+                            }
+                        }
+                        _ => {}
+                    }
+                    j += 1;
+                }
+            }
+            while j < bytes.len() && bytes[j] == b' ' {
+                j += 1;
+            }
+            if bytes.get(j) == Some(&b'(') {
+                let name = path.rsplit("::").next().unwrap_or(path).to_string();
+                if !is_method {
+                    calls.push((name, path.to_string()));
+                } else if is_self_receiver(&clause[..start - 1]) {
+                    calls.push((name, format!("Self::{}", path)));
+                }
+            }
+            i = end;
+        }
+    }
+    calls
+}
+
+///  Whether the text before a method call's `.` ends with the receiver `self`
+///  itself, not e.g. `self.field`, `myself` or `self@`.
+fn is_self_receiver(before: &str) -> bool {
+    let Some(rest) = before.strip_suffix("self") else {
+        return false;
+    };
+    !rest.ends_with(|c: char| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn links_are_parsed_in_all_three_forms() {
+        let line = "see [`f`] and [the lemma](crate::a::lemma) or [g][`b::g`].";
+        let shortcut = parse_link(line, 4).unwrap();
+        assert_eq!((shortcut.label, shortcut.target, shortcut.form), ("`f`", "`f`", LinkForm::Shortcut));
+        assert_eq!(&line[shortcut.end..], " and [the lemma](crate::a::lemma) or [g][`b::g`].");
+
+        let inline = parse_link(line, line.find("[the").unwrap()).unwrap();
+        assert_eq!((inline.label, inline.target, inline.form), ("the lemma", "crate::a::lemma", LinkForm::Inline));
+        assert_eq!(&line[inline.end..], " or [g][`b::g`].");
+
+        let reference = parse_link(line, line.find("[g]").unwrap()).unwrap();
+        assert_eq!((reference.label, reference.target, reference.form), ("g", "`b::g`", LinkForm::Reference));
+        assert_eq!(&line[reference.end..], ".");
+
+        assert!(parse_link("[] and [a[b]]", 0).is_none());
+        assert!(parse_link("[unclosed", 0).is_none());
+    }
+
+    #[test]
+    fn link_targets_are_reduced_to_paths() {
+        assert_eq!(link_path("`crate::a::f`").as_deref(), Some("crate::a::f"));
+        assert_eq!(link_path("fn@f()").as_deref(), Some("f"));
+        assert_eq!(link_path("`assert_seqs!`").as_deref(), Some("assert_seqs"));
+        assert_eq!(link_path("https://example.com"), None);
+        assert_eq!(link_path("a b"), None);
+        assert_eq!(link_path("1st"), None);
+    }
+
+    #[test]
+    fn shortcut_prose_is_not_identifier_like() {
+        assert!(is_identifier_like("seq_len"));
+        assert!(is_identifier_like("SeqChar"));
+        assert!(is_identifier_like("a::b"));
+        assert!(!is_identifier_like("note"));
+        assert!(!is_identifier_like("Optional"));
+        assert!(!is_identifier_like("TODO"));
+    }
+
+    #[test]
+    fn links_resolve_in_each_style() {
+        let lemma = RtDocItem {
+            name: "lemma".to_string(),
+            module_path: "crate::a".to_string(),
+            anchor: "fn.lemma".to_string(),
+            ..Default::default()
+        };
+        let caller = RtDocItem {
+            name: "f".to_string(),
+            module_path: "crate::b".to_string(),
+            anchor: "fn.f".to_string(),
+            doc_comment: Some("Uses [`lemma`] and [module a](super::a), not [note] or `[lemma]`.".to_string()),
+            ..Default::default()
+        };
+        let output = RtDocOutput {
+            modules: vec![
+                RtDocModule { path: "crate::a".to_string(), doc: None, items: vec![lemma] },
+                RtDocModule { path: "crate::b".to_string(), doc: None, items: vec![caller] },
+            ],
+        };
+
+        let expected = [
+            (LinkStyle::Markdown, "#crate.a.fn.lemma", "#crate.a"),
+            (LinkStyle::MdBook, "crate.a.md#crate.a.fn.lemma", "crate.a.md"),
+            (LinkStyle::Html, "crate.a.html#fn.lemma", "crate.a.html"),
+        ];
+        for (style, lemma_url, module_url) in expected {
+            let mut output = output.clone();
+            resolve_doc_links(&mut output, style);
+            assert_eq!(
+                output.modules[1].items[0].doc_comment.as_deref().unwrap(),
+                format!("Uses [`lemma`]({}) and [module a]({}), not [note] or `[lemma]`.", lemma_url, module_url)
+            );
+        }
+    }
+
+    #[test]
+    fn calls_are_found_in_clauses() {
+        let clauses = vec![
+            "f(x) && a::g::<T>(y) && x.h() && self.k(z) && self.v.m() && s@.len() > 0".to_string(),
+            "\"p(q)\" == str && T::MAX > 0 && inner (x)".to_string(),
+        ];
+        let calls: Vec<(String, String)> = clause_calls(&clauses);
+        let expected = [("f", "f"), ("g", "a::g"), ("k", "Self::k"), ("inner", "inner")];
+        assert_eq!(calls, expected.map(|(n, p)| (n.to_string(), p.to_string())));
+    }
+
+    #[test]
+    fn only_a_bare_self_is_a_self_receiver() {
+        assert!(is_self_receiver("self"));
+        assert!(is_self_receiver("x == self"));
+        assert!(is_self_receiver("(self"));
+        assert!(!is_self_receiver("self.v"));
+        assert!(!is_self_receiver("myself"));
+        assert!(!is_self_receiver("a.self"));
+        assert!(!is_self_receiver("self@"));
+    }
+
+    #[test]
+    fn type_paths_in_order() {
+        assert_eq!(type_paths("Seq<crate::a::Foo>"), vec!["Seq", "crate::a::Foo"]);
+        assert_eq!(type_paths("&'a mut Map<Self::Key, [u8; 4]>"), vec!["mut", "Map", "Self::Key", "u8"]);
+        assert_eq!(type_paths("Box<dyn Fn(T) -> a::B>"), vec!["Box", "dyn", "Fn", "T", "a::B"]);
+        assert_eq!(type_paths("a::"), vec!["a"]);
+    }
+}
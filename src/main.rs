@@ -1,52 +1,137 @@
-use verus_docgenerator::tree_sitter_wrapper;
+use verus_docgenerator::coverage;
 use verus_docgenerator::doc_item::*;
 use verus_docgenerator::extraction;
+use verus_docgenerator::graph;
+use verus_docgenerator::html;
+use verus_docgenerator::json;
+use verus_docgenerator::links::{self, LinkStyle};
 use verus_docgenerator::markdown;
+use verus_docgenerator::mdbook;
+use verus_docgenerator::module_tree;
+use verus_docgenerator::trust;
+use verus_docgenerator::visibility;
 
-use std::path::{Path, PathBuf};
-
-fn collect_rs_files(dir: &Path) -> Vec<PathBuf> {
-    let mut files = Vec::new();
-    if let Ok(entries) = std::fs::read_dir(dir) {
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path.is_dir() {
-                files.extend(collect_rs_files(&path));
-            } else if path.extension().map_or(false, |e| e == "rs") {
-                files.push(path);
-            }
+use std::path::Path;
+
+///  Output backends selectable with `--format`.
+enum OutputFormat {
+    Markdown,
+    Json,
+    ///  A directory of pages rather than a single file.
+    Html,
+    ///  A directory of Markdown chapters with a `SUMMARY.md`.
+    MdBook,
+}
+
+impl OutputFormat {
+    fn default_output(&self) -> &'static str {
+        match self {
+            OutputFormat::Markdown => "docs.md",
+            OutputFormat::Json => "docs.json",
+            OutputFormat::Html => "docs",
+            OutputFormat::MdBook => "book",
+        }
+    }
+
+    ///  How intra-doc links are written; None keeps docs as written.
+    fn link_style(&self) -> Option<LinkStyle> {
+        match self {
+            OutputFormat::Markdown => Some(LinkStyle::Markdown),
+            OutputFormat::Json => None,
+            OutputFormat::Html => Some(LinkStyle::Html),
+            OutputFormat::MdBook => Some(LinkStyle::MdBook),
+        }
+    }
+}
+
+///  The value given for the flag at `args[i - 1]`. Exits with a usage error
+///  if it is missing, including when another flag follows instead.
+fn flag_value(args: &[String], i: usize) -> &str {
+    match args.get(i) {
+        Some(value) if !value.starts_with("--") => value,
+        _ => {
+            eprintln!("Missing value for {}", args[i - 1]);
+            std::process::exit(1);
         }
     }
-    files
 }
 
 fn main() {
     let args: Vec<String> = std::env::args().collect();
 
     let mut input_dir = String::from(".");
-    let mut output_file = String::from("docs.md");
-    let mut src_prefix = String::from("./src/");
+    let mut output_file: Option<String> = None;
+    let mut src_prefix: Option<String> = None;
+    let mut min_visibility = RtVisibility::Public;
+    let mut format = OutputFormat::Markdown;
+    let mut lemma_graph_file: Option<String> = None;
+    let mut lemma_graph_html = false;
+    let mut trust_report_file: Option<String> = None;
+    //  Check doc coverage instead of writing docs; Some(threshold) in percent
+    let mut min_coverage: Option<f64> = None;
 
     let mut i = 1;
     while i < args.len() {
         match args[i].as_str() {
             "--input" | "-i" => {
                 i += 1;
-                if i < args.len() {
-                    input_dir = args[i].clone();
-                }
+                input_dir = flag_value(&args, i).to_string();
             }
             "--output" | "-o" => {
                 i += 1;
-                if i < args.len() {
-                    output_file = args[i].clone();
-                }
+                output_file = Some(flag_value(&args, i).to_string());
             }
             "--src-prefix" => {
                 i += 1;
-                if i < args.len() {
-                    src_prefix = args[i].clone();
-                }
+                src_prefix = Some(flag_value(&args, i).to_string());
+            }
+            "--min-visibility" => {
+                i += 1;
+                min_visibility = match RtVisibility::from_level(flag_value(&args, i)) {
+                    Some(v) => v,
+                    None => {
+                        eprintln!(
+                            "Unknown visibility level: {} (expected public, crate, restricted or private)",
+                            args[i]
+                        );
+                        std::process::exit(1);
+                    }
+                };
+            }
+            "--format" | "-f" => {
+                i += 1;
+                format = match flag_value(&args, i) {
+                    "md" | "markdown" => OutputFormat::Markdown,
+                    "json" => OutputFormat::Json,
+                    "html" => OutputFormat::Html,
+                    "mdbook" => OutputFormat::MdBook,
+                    other => {
+                        eprintln!("Unknown format: {} (expected md, json, html or mdbook)", other);
+                        std::process::exit(1);
+                    }
+                };
+            }
+            "--lemma-graph" => {
+                i += 1;
+                lemma_graph_file = Some(flag_value(&args, i).to_string());
+            }
+            "--lemma-graph-html" => lemma_graph_html = true,
+            "--trust-report" => {
+                i += 1;
+                trust_report_file = Some(flag_value(&args, i).to_string());
+            }
+            "--coverage" => {
+                min_coverage.get_or_insert(0.0);
+            }
+            "--min-coverage" => {
+                i += 1;
+                min_coverage = match flag_value(&args, i).trim_end_matches('%').parse::<f64>() {
+                    Ok(threshold) if (0.0..=100.0).contains(&threshold) => Some(threshold),
+                    _ => {
+                        eprintln!("Invalid coverage threshold: {} (expected a percentage from 0 to 100)", args[i]);
+                        std::process::exit(1);
+                    }
+                };
             }
             _ => {
                 eprintln!("Unknown argument: {}", args[i]);
@@ -62,60 +147,140 @@ fn main() {
         std::process::exit(1);
     }
 
-    let rs_files = collect_rs_files(input_path);
-    eprintln!("Found {} .rs files in {}", rs_files.len(), input_dir);
+    //  Book chapters link to sources from their own directory, not the working one
+    let src_prefix = src_prefix.unwrap_or_else(|| match format {
+        OutputFormat::MdBook => {
+            let out_dir = output_file.as_deref().unwrap_or(format.default_output());
+            mdbook::relative_src_prefix(Path::new(out_dir), input_path)
+        }
+        _ => String::from("./src/"),
+    });
 
-    let mut all_items: Vec<RtDocItem> = Vec::new();
+    //  Walk the module tree from the crate root so item paths match `use` paths
+    let loaded = match module_tree::find_crate_root(input_path) {
+        Some(root_file) => {
+            eprintln!("Crate root: {}", root_file.display());
+            module_tree::load_crate(input_path, &root_file)
+        }
+        None => {
+            eprintln!(
+                "No lib.rs or main.rs found in {}; documenting every .rs file, with module paths taken from file paths",
+                input_dir
+            );
+            module_tree::load_files(input_path)
+        }
+    };
+    let mut all_items: Vec<RtDocItem> = loaded.items;
 
-    for file_path in &rs_files {
-        let source = match std::fs::read_to_string(file_path) {
-            Ok(s) => s,
-            Err(e) => {
-                eprintln!("Warning: could not read {}: {}", file_path.display(), e);
-                continue;
-            }
-        };
+    eprintln!("Extracted {} total items", all_items.len());
 
-        let rel_path = file_path
-            .strip_prefix(input_path)
-            .unwrap_or(file_path)
-            .to_string_lossy()
-            .to_string();
-
-        let module_path = rel_path
-            .trim_end_matches(".rs")
-            .replace('/', "::")
-            .replace("mod::", "")
-            .replace("::mod", "")
-            .replace("lib", "crate");
-
-        match tree_sitter_wrapper::extract_items(&source, &rel_path, &module_path) {
-            Ok(items) => {
-                all_items.extend(items);
-            }
+    //  The trusted base covers private code too, so report it before filtering
+    if let Some(ref path) = trust_report_file {
+        match std::fs::write(path, trust::format_report(&all_items, &src_prefix)) {
+            Ok(()) => eprintln!("Wrote trusted base report to {}", path),
             Err(e) => {
-                eprintln!("Warning: parse error in {}: {}", file_path.display(), e);
+                eprintln!("Error writing {}: {}", path, e);
+                std::process::exit(1);
             }
         }
     }
 
-    eprintln!("Extracted {} total items", all_items.len());
+    //  Invariant functions are usually private; attach them to their types first
+    extraction::attach_invariants(&mut all_items);
 
-    //  Filter to public items only
-    let public_items: Vec<RtDocItem> = all_items
-        .into_iter()
-        .filter(|item| matches!(item.visibility, RtVisibility::Public))
-        .collect();
+    //  Calls between visible lemmas often go through private ones, so the
+    //  lemma graph is resolved over every item
+    let all_output = (lemma_graph_file.is_some() || lemma_graph_html).then(|| {
+        let items = all_items.iter().filter(|i| i.item_kind != RtItemKind::Use).cloned().collect();
+        extraction::build_doc_output(items, None)
+    });
 
-    eprintln!("{} public items", public_items.len());
+    //  Keep items reachable from the crate root, including `pub use` re-exports
+    let visible_items: Vec<RtDocItem> =
+        visibility::effective_visible_items(all_items, min_visibility.clone());
+
+    eprintln!("{} items visible at {}", visible_items.len(), min_visibility.level());
 
     //  Group by module and sort
-    let output = extraction::build_doc_output(public_items);
+    let mut output = extraction::build_doc_output(visible_items, loaded.doc);
+
+    //  In coverage mode, report on the visible items and stop
+    if let Some(threshold) = min_coverage {
+        let report = coverage::compute(&output);
+        print!("{}", coverage::format_report(&report));
+        if report.total.percent() < threshold {
+            eprintln!(
+                "Doc coverage {:.1}% is below the required {:.1}%",
+                report.total.percent(),
+                threshold
+            );
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let output_file = output_file.unwrap_or_else(|| format.default_output().to_string());
+
+    //  Turn `[`name`]` references in docs into links for the rendered formats
+    let link_style = format.link_style();
+    if let Some(style) = link_style {
+        links::resolve_doc_links(&mut output, style);
+    }
+    //  JSON only records the paths of contract, group and type references, so any style will do
+    links::resolve_contract_refs(&mut output, link_style.unwrap_or(LinkStyle::Html));
+    links::resolve_broadcast_groups(&mut output, link_style.unwrap_or(LinkStyle::Html));
+    links::resolve_type_refs(&mut output, link_style.unwrap_or(LinkStyle::Html));
+
+    let lemma_graph = all_output.as_ref().map(|all| graph::build_lemma_graph(&output, all));
+    if let (Some(path), Some(lemma_graph)) = (&lemma_graph_file, &lemma_graph) {
+        //  `.json` gets JSON, anything else Graphviz DOT
+        let rendered = if path.ends_with(".json") {
+            graph::to_json(lemma_graph)
+        } else {
+            graph::to_dot(lemma_graph)
+        };
+        match std::fs::write(path, rendered) {
+            Ok(()) => eprintln!(
+                "Wrote lemma graph ({} lemmas, {} calls) to {}",
+                lemma_graph.nodes.len(),
+                lemma_graph.edges.len(),
+                path
+            ),
+            Err(e) => {
+                eprintln!("Error writing {}: {}", path, e);
+                std::process::exit(1);
+            }
+        }
+    }
 
-    //  Generate markdown
-    let md = markdown::format_doc_output(&output, &src_prefix);
+    //  Render in the requested format
+    let rendered = match format {
+        OutputFormat::Markdown => markdown::format_doc_output(&output, &src_prefix),
+        OutputFormat::Json => json::format_doc_output(&output),
+        OutputFormat::Html => {
+            let graph = lemma_graph.as_ref().filter(|_| lemma_graph_html);
+            match html::write_site(&output, Path::new(&output_file), &src_prefix, graph) {
+                Ok(()) => eprintln!("Wrote HTML documentation to {}", output_file),
+                Err(e) => {
+                    eprintln!("Error writing {}: {}", output_file, e);
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        OutputFormat::MdBook => {
+            match mdbook::write_book(&output, Path::new(&output_file), &src_prefix) {
+                Ok(()) => eprintln!("Wrote mdBook chapters to {}", output_file),
+                Err(e) => {
+                    eprintln!("Error writing {}: {}", output_file, e);
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+    };
 
-    match std::fs::write(&output_file, &md) {
+    match std::fs::write(&output_file, &rendered) {
         Ok(()) => eprintln!("Wrote documentation to {}", output_file),
         Err(e) => {
             eprintln!("Error writing {}: {}", output_file, e);
@@ -1,4 +1,6 @@
 use crate::doc_item::*;
+use crate::links;
+use crate::trust;
 
 ///  Generate a markdown document from the documentation output.
 ///  `src_prefix` is prepended to file paths to form links, e.g. `./src/`.
@@ -7,81 +9,374 @@ pub fn format_doc_output(output: &RtDocOutput, src_prefix: &str) -> String {
 
     md.push_str("# API Reference\n\n");
     md.push_str("*Auto-generated by verus-docgenerator*\n\n");
+    if let Some(intro) = output.intro() {
+        format_module_doc(&mut md, intro, 1);
+    }
 
     for module in &output.modules {
+        md.push_str(&format!("<a id=\"{}\"></a>\n\n", links::page_stem(&module.path)));
+        //  A crate root with only a doc has nothing to show below the intro
+        if module.path == "crate" && module.items.is_empty() {
+            continue;
+        }
         md.push_str(&format!("## `{}`\n\n", module.path));
+        //  The crate root's doc is already the intro
+        if let Some(doc) = module.doc.as_deref().filter(|_| module.path != "crate") {
+            format_module_doc(&mut md, doc, 2);
+        }
+
+        for section in module.sections() {
+            let title = match section.impl_block {
+                Some(_) => format!("`{}`", section.title),
+                None => section.title,
+            };
+            format_section(&mut md, &title, &section.items, &module.path, src_prefix);
+        }
+    }
+
+    md
+}
+
+///  Format a titled section of the items on the page of module `page`.
+fn format_section(md: &mut String, title: &str, items: &[&RtDocItem], page: &str, src_prefix: &str) {
+    md.push_str(&format!("### {}\n\n", title));
+    for item in items {
+        format_item(md, item, page, src_prefix);
+    }
+    md.push('\n');
+}
+
+///  Format a single documentation item as a markdown list entry with a clickable link.
+///  `page` is the module the item is listed under, which prefixes its link target id.
+///  Also used by the mdBook output for each module chapter.
+pub fn format_item(md: &mut String, item: &RtDocItem, page: &str, src_prefix: &str) {
+    md.push_str(&format!(
+        "- <a id=\"{id}\"></a>**`{title}`** — {link}",
+        id = links::markdown_id(page, &item.anchor),
+        title = item.title(),
+        link = source_link(&item.file_path, item.line_number, src_prefix),
+    ));
+
+    md.push('\n');
+
+    if !item.trust.is_empty() {
+        md.push_str(&format!("  ⚠ *Trusted*: {}\n", format_trust(&item.trust)));
+    }
+
+    if let Some(ref original) = item.reexport_of {
+        md.push_str(&format!("  *Re-exported from `{}`*\n", original));
+    }
+
+    if !item.signature.is_empty() {
+        md.push_str(&format!("  ```rust\n  {}\n  ```\n", item.declaration()));
+    }
+
+    if !item.type_refs.is_empty() {
+        md.push_str(&format!("  *Refers to*: {}\n", format_xrefs(&item.type_refs, |r| &r.path)));
+    }
+
+    if let Some(ref doc) = item.doc_comment {
+        format_doc(md, doc, 2);
+    }
+
+    format_models(md, item);
+    format_invariants(md, item);
+    format_contract(md, item);
+    format_broadcast(md, item);
+    format_type_members(md, item);
+    format_trait_members(md, item, page, src_prefix);
+}
+
+///  Format trusted sites as a list of kinds with counts, e.g. `` `assume` ×2``.
+fn format_trust(sites: &[RtTrustSite]) -> String {
+    let kinds: Vec<String> = trust::summary(sites)
+        .into_iter()
+        .map(|(kind, count)| match count {
+            1 => format!("`{}`", kind.as_str()),
+            n => format!("`{}` ×{}", kind.as_str(), n),
+        })
+        .collect();
+    kinds.join(", ")
+}
+
+///  Format a `[file:line](link)` reference to an item's source location.
+fn source_link(file_path: &str, line: usize, src_prefix: &str) -> String {
+    let sep = if src_prefix.is_empty() || src_prefix.ends_with('/') { "" } else { "/" };
+    format!("[{file}:{line}]({prefix}{sep}{file}#L{line})",
+        prefix = src_prefix,
+        sep = sep,
+        file = file_path,
+        line = line,
+    )
+}
 
-        //  Group items by kind within each module
-        let spec_items: Vec<&RtDocItem> = module
-            .items
-            .iter()
-            .filter(|i| matches!(i.kind, RtFnKind::Spec))
-            .collect();
-        let proof_items: Vec<&RtDocItem> = module
-            .items
-            .iter()
-            .filter(|i| matches!(i.kind, RtFnKind::Proof))
-            .collect();
-        let exec_items: Vec<&RtDocItem> = module
-            .items
-            .iter()
-            .filter(|i| matches!(i.kind, RtFnKind::Exec))
-            .collect();
-
-        if !spec_items.is_empty() {
-            md.push_str("### Spec Functions\n\n");
-            for item in &spec_items {
-                format_item(&mut md, item, src_prefix);
+///  Format the associated types, methods and known impls of a trait.
+fn format_trait_members(md: &mut String, item: &RtDocItem, page: &str, src_prefix: &str) {
+    let assoc_types: Vec<&RtDocItem> = item
+        .members
+        .iter()
+        .filter(|m| m.item_kind == RtItemKind::AssocType)
+        .collect();
+    if !assoc_types.is_empty() {
+        md.push_str("  - *associated types*\n");
+        for assoc in assoc_types {
+            md.push_str(&format!("    - `{}`", assoc.signature));
+            format_member_doc(md, &assoc.doc_comment);
+        }
+    }
+
+    let (required, provided) = item.trait_methods();
+    for (label, methods) in [("required methods", required), ("provided methods", provided)] {
+        if methods.is_empty() {
+            continue;
+        }
+        md.push_str(&format!("  - *{}*\n", label));
+        for method in methods {
+            let mut nested = String::new();
+            format_item(&mut nested, method, page, src_prefix);
+            for line in nested.lines() {
+                if line.is_empty() {
+                    md.push('\n');
+                } else {
+                    md.push_str(&format!("    {}\n", line));
+                }
             }
-            md.push('\n');
         }
+    }
 
-        if !proof_items.is_empty() {
-            md.push_str("### Proof Functions\n\n");
-            for item in &proof_items {
-                format_item(&mut md, item, src_prefix);
+    if !item.impls.is_empty() {
+        md.push_str("  - *implementations*\n");
+        for imp in &item.impls {
+            md.push_str(&format!(
+                "    - `{}` — {}\n",
+                imp.signature,
+                source_link(&imp.file_path, imp.line_number, src_prefix),
+            ));
+        }
+    }
+}
+
+///  Format the abstract models of a type: the `View`/`DeepView` type `V`
+///  and the body of its `view` function.
+fn format_models(md: &mut String, item: &RtDocItem) {
+    for model in &item.models {
+        let what = if model.trait_name == "DeepView" { "self.deep_view()" } else { "self@" };
+        md.push_str(&format!("  **Abstract model** (`{}`)", model.header));
+        match model.model_type {
+            Some(ref ty) => md.push_str(&format!(": `{}` is a `{}`\n", what, ty)),
+            None => md.push('\n'),
+        }
+        if !model.type_refs.is_empty() {
+            md.push_str(&format!("  *Refers to*: {}\n", format_xrefs(&model.type_refs, |r| &r.path)));
+        }
+        match model.view_body {
+            Some(ref body) => {
+                md.push_str("  ```rust\n");
+                for line in body.lines() {
+                    if line.is_empty() {
+                        md.push('\n');
+                    } else {
+                        md.push_str(&format!("  {}\n", line));
+                    }
+                }
+                md.push_str("  ```\n");
             }
-            md.push('\n');
+            None => md.push_str("  *The view function is closed.*\n"),
         }
+        md.push('\n');
+    }
+}
 
-        if !exec_items.is_empty() {
-            md.push_str("### Exec Functions\n\n");
-            for item in &exec_items {
-                format_item(&mut md, item, src_prefix);
+///  Format the invariants of a type, each with its predicate's body when open.
+fn format_invariants(md: &mut String, item: &RtDocItem) {
+    if item.invariants.is_empty() {
+        return;
+    }
+    md.push_str("  **Invariant**\n\n");
+    for invariant in &item.invariants {
+        md.push_str(&format!("  - `{}` — *{}*", invariant.signature, invariant.kind.as_str()));
+        match invariant.doc_comment {
+            Some(ref doc) => {
+                md.push('\n');
+                format_doc(md, doc, 4);
             }
+            None => md.push('\n'),
+        }
+        if let Some(ref body) = invariant.body {
+            md.push_str("    ```rust\n");
+            for line in body.lines() {
+                if line.is_empty() {
+                    md.push('\n');
+                } else {
+                    md.push_str(&format!("    {}\n", line));
+                }
+            }
+            md.push_str("    ```\n");
+        }
+    }
+    md.push('\n');
+}
+
+///  Format the derives, fields and variants of a struct or enum.
+fn format_type_members(md: &mut String, item: &RtDocItem) {
+    if !item.derives.is_empty() {
+        let derives: Vec<String> = item.derives.iter().map(|d| format!("`{}`", d)).collect();
+        md.push_str(&format!("  - *derives*: {}\n", derives.join(", ")));
+    }
+
+    if !item.fields.is_empty() {
+        md.push_str("  - *fields*\n");
+        for field in &item.fields {
+            let vis = field.visibility.as_string();
+            let sep = if vis.is_empty() { "" } else { " " };
+            md.push_str(&format!("    - `{}{}{}: {}`", vis, sep, field.name, field.ty));
+            format_member_doc(md, &field.doc_comment);
+        }
+    }
+
+    if !item.variants.is_empty() {
+        md.push_str("  - *variants*\n");
+        for variant in &item.variants {
+            md.push_str(&format!("    - `{}`", variant.signature));
+            format_member_doc(md, &variant.doc_comment);
+        }
+    }
+}
+
+///  Finish a field or variant entry with its doc comment, if any. One-line
+///  docs stay on the entry's line; longer ones are nested beneath it.
+fn format_member_doc(md: &mut String, doc: &Option<String>) {
+    match doc {
+        Some(doc) if !doc.contains('\n') => md.push_str(&format!(" — {}\n", doc)),
+        Some(doc) => {
             md.push('\n');
+            format_doc(md, doc, 6);
         }
+        None => md.push('\n'),
     }
+}
 
-    md
+///  Append a doc comment as Markdown nested under a list entry, with every
+///  line indented by `indent` spaces and set off by blank lines.
+fn format_doc(md: &mut String, doc: &str, indent: usize) {
+    md.push('\n');
+    for line in demote_headings(doc, 3).lines() {
+        if line.is_empty() {
+            md.push('\n');
+        } else {
+            md.push_str(&format!("{:indent$}{}\n", "", line, indent = indent));
+        }
+    }
+    md.push('\n');
 }
 
-///  Format a single documentation item as a markdown list entry with a clickable link.
-fn format_item(md: &mut String, item: &RtDocItem, src_prefix: &str) {
-    let open_str = if item.is_open { "open " } else { "" };
-    let kind_str = item.kind.as_str();
+///  Append a module doc as top-level Markdown below a heading of level
+///  `heading_level`. Also used by the mdBook output for module chapters.
+pub fn format_module_doc(md: &mut String, doc: &str, heading_level: usize) {
+    md.push_str(&demote_headings(doc, heading_level));
+    md.push_str("\n\n");
+}
 
-    let sep = if src_prefix.is_empty() || src_prefix.ends_with('/') { "" } else { "/" };
-    let link = format!("{}{sep}{file}#L{line}",
-        src_prefix,
-        sep = sep,
-        file = item.file_path,
-        line = item.line_number,
-    );
+///  Push `#` headings in a doc comment `levels` levels down (at most `######`)
+///  so they nest below the surrounding headings. Lines inside code fences
+///  are left alone.
+fn demote_headings(doc: &str, levels: usize) -> String {
+    let mut out = Vec::new();
+    let mut in_fence = false;
+    for line in doc.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+        }
+        let level = line.len() - line.trim_start_matches('#').len();
+        let is_heading = !in_fence && level > 0 && line[level..].starts_with(' ');
+        if is_heading {
+            out.push(format!("{}{}", "#".repeat((level + levels).min(6)), &line[level..]));
+        } else {
+            out.push(line.to_string());
+        }
+    }
+    out.join("\n")
+}
 
-    md.push_str(&format!(
-        "- **`{open}{kind} fn {name}`** — [{file}:{line}]({link})",
-        open = open_str,
-        kind = kind_str,
-        name = item.name,
-        file = item.file_path,
-        line = item.line_number,
-        link = link,
-    ));
+///  Format the requires/ensures/etc. clauses of an item as nested lists,
+///  followed by links to the spec functions they call and to the items
+///  whose contracts call this one.
+fn format_contract(md: &mut String, item: &RtDocItem) {
+    for (keyword, clauses) in item.contract.clauses() {
+        if clauses.is_empty() {
+            continue;
+        }
+        md.push_str(&format!("  - *{}*\n", keyword));
+        for clause in clauses {
+            md.push_str(&format!("    - `{}`\n", clause));
+        }
+    }
 
-    if let Some(ref doc) = item.doc_comment {
-        md.push_str(&format!("\n  > {}\n", doc));
-    } else {
-        md.push('\n');
+    if !item.contract_refs.is_empty() {
+        md.push_str(&format!("  - *uses*: {}\n", format_xrefs(&item.contract_refs, |r| &r.name)));
+    }
+    if !item.used_in.is_empty() {
+        md.push_str(&format!("  - *used in contracts of*: {}\n", format_xrefs(&item.used_in, |r| &r.path)));
+    }
+}
+
+///  Format the members of a broadcast group and the groups a lemma or group
+///  belongs to.
+fn format_broadcast(md: &mut String, item: &RtDocItem) {
+    if !item.group_members.is_empty() {
+        md.push_str("  - *members*\n");
+        for member in &item.group_members {
+            md.push_str(&format!("    - {}\n", format_xref(member, &member.name)));
+        }
+    }
+    if !item.in_groups.is_empty() {
+        md.push_str(&format!("  - *in broadcast groups*: {}\n", format_xrefs(&item.in_groups, |r| &r.path)));
+    }
+}
+
+///  Format references as a comma-separated list of links labelled by `label`.
+fn format_xrefs(refs: &[RtXref], label: impl Fn(&RtXref) -> &String) -> String {
+    let links: Vec<String> = refs.iter().map(|r| format_xref(r, label(r))).collect();
+    links.join(", ")
+}
+
+///  Format a reference as a link labelled `label`, or as code when it has no
+///  link, e.g. outside the crate.
+fn format_xref(r: &RtXref, label: &str) -> String {
+    match r.url.as_str() {
+        "" => format!("`{}`", label),
+        url => format!("[`{}`]({})", label, url),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn headings_are_demoted_outside_code_fences() {
+        let doc = "# Title\n```\n# comment\n```\n#### Deep\n#tag";
+        assert_eq!(demote_headings(doc, 3), "#### Title\n```\n# comment\n```\n###### Deep\n#tag");
+    }
+
+    #[test]
+    fn item_docs_are_indented_under_their_bullet() {
+        let mut md = String::new();
+        format_doc(&mut md, "Summary.\n\n- point\n\n```\nfn f() {}\n```", 2);
+        assert_eq!(md, "\n  Summary.\n\n  - point\n\n  ```\n  fn f() {}\n  ```\n\n");
+    }
+
+    #[test]
+    fn crate_root_with_only_a_doc_gets_no_heading() {
+        let output = RtDocOutput {
+            modules: vec![
+                RtDocModule { path: "crate".to_string(), doc: Some("Intro.".to_string()), items: vec![] },
+                RtDocModule { path: "crate::a".to_string(), doc: Some("Module a.".to_string()), items: vec![] },
+            ],
+        };
+        let md = format_doc_output(&output, "./src/");
+        assert!(md.contains("Intro.\n\n<a id=\"crate\"></a>\n\n<a id=\"crate.a\"></a>\n\n## `crate::a`\n\nModule a."));
+        assert!(!md.contains("## `crate`"));
     }
 }
@@ -0,0 +1,177 @@
+use crate::doc_item::*;
+use crate::markdown;
+
+use std::collections::BTreeMap;
+use std::path::{Component, Path};
+
+///  Write an mdBook-compatible directory: `SUMMARY.md` listing one Markdown
+///  file per module, nested by module path. All links between pages are
+///  relative, so the directory can be dropped into a book's `src/`.
+///  `src_prefix` is prepended to file paths to form source links; since the
+///  links are followed from the chapters, see `relative_src_prefix`.
+pub fn write_book(output: &RtDocOutput, out_dir: &Path, src_prefix: &str) -> std::io::Result<()> {
+    std::fs::create_dir_all(out_dir)?;
+    std::fs::write(out_dir.join("SUMMARY.md"), format_summary(output))?;
+
+    for module in &output.modules {
+        let page = format_module_page(output, module, src_prefix);
+        std::fs::write(out_dir.join(module_file_name(&module.path)), page)?;
+    }
+
+    Ok(())
+}
+
+///  The input directory as seen from chapters written to `out_dir`, e.g.
+///  `../../src/` for `-i src -o book/api`, for use as the default `src_prefix`.
+///  Falls back to the input directory as given if either path can't be made
+///  absolute.
+pub fn relative_src_prefix(out_dir: &Path, input_dir: &Path) -> String {
+    let fallback = || format!("{}/", input_dir.display());
+    let (Ok(from), Ok(to)) = (std::path::absolute(out_dir), std::path::absolute(input_dir)) else {
+        return fallback();
+    };
+    let (from, to) = (normalize(&from), normalize(&to));
+    //  Different roots, e.g. drives on Windows
+    if from.first() != to.first() {
+        return fallback();
+    }
+
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    let mut prefix = "../".repeat(from.len() - common);
+    for segment in &to[common..] {
+        prefix.push_str(segment);
+        prefix.push('/');
+    }
+    if prefix.is_empty() {
+        prefix.push_str("./");
+    }
+    prefix
+}
+
+///  Path components with `.` and `..` applied lexically.
+fn normalize(path: &Path) -> Vec<String> {
+    let mut segments: Vec<String> = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if segments.len() > 1 {
+                    segments.pop();
+                }
+            }
+            other => segments.push(other.as_os_str().to_string_lossy().to_string()),
+        }
+    }
+    segments
+}
+
+///  File name of a module's chapter, e.g. `crate.foo.bar.md`.
+pub fn module_file_name(module_path: &str) -> String {
+    format!("{}.md", module_path.replace("::", "."))
+}
+
+///  The nearest enclosing module of `path` that has its own page. Modules
+///  without documented items get no page, so their children move up a level.
+fn parent_page<'a>(output: &'a RtDocOutput, path: &str) -> Option<&'a str> {
+    let mut prefix = path;
+    while let Some((parent, _)) = prefix.rsplit_once("::") {
+        if let Some(module) = output.modules.iter().find(|m| m.path == parent) {
+            return Some(&module.path);
+        }
+        prefix = parent;
+    }
+    None
+}
+
+#[derive(Default)]
+struct TreeNode {
+    has_page: bool,
+    children: BTreeMap<String, TreeNode>,
+}
+
+///  Format `SUMMARY.md`. Modules are nested under their parent module's
+///  chapter and listed depth-first, so `a::x` follows `a` rather than `a1`.
+///  mdBook requires each nesting level to step by one, so modules without a
+///  page don't add a level.
+fn format_summary(output: &RtDocOutput) -> String {
+    let mut root = TreeNode::default();
+    for module in &output.modules {
+        let mut node = &mut root;
+        for segment in module.path.split("::") {
+            node = node.children.entry(segment.to_string()).or_default();
+        }
+        node.has_page = true;
+    }
+
+    let mut md = String::from("# API Reference\n\n");
+    format_summary_node(&mut md, &root, "", 0);
+    md
+}
+
+fn format_summary_node(md: &mut String, node: &TreeNode, prefix: &str, depth: usize) {
+    for (name, child) in &node.children {
+        let path = if prefix.is_empty() {
+            name.clone()
+        } else {
+            format!("{}::{}", prefix, name)
+        };
+        if child.has_page {
+            md.push_str(&format!("{}- [{}]({})\n", "  ".repeat(depth), path, module_file_name(&path)));
+            format_summary_node(md, child, &path, depth + 1);
+        } else {
+            format_summary_node(md, child, &path, depth);
+        }
+    }
+}
+
+fn format_module_page(output: &RtDocOutput, module: &RtDocModule, src_prefix: &str) -> String {
+    let mut md = String::new();
+    md.push_str(&format!("# `{}`\n\n", module.path));
+    if let Some(ref doc) = module.doc {
+        markdown::format_module_doc(&mut md, doc, 1);
+    }
+
+    if let Some(parent) = parent_page(output, &module.path) {
+        md.push_str(&format!("Parent: [`{}`]({})\n\n", parent, module_file_name(parent)));
+    }
+
+    let children: Vec<&RtDocModule> = output
+        .modules
+        .iter()
+        .filter(|m| parent_page(output, &m.path) == Some(module.path.as_str()))
+        .collect();
+    if !children.is_empty() {
+        md.push_str("## Modules\n\n");
+        for child in children {
+            md.push_str(&format!("- [`{}`]({})\n", child.path, module_file_name(&child.path)));
+        }
+        md.push('\n');
+    }
+
+    for section in module.sections() {
+        let title = match section.impl_block {
+            Some(_) => format!("`{}`", section.title),
+            None => section.title,
+        };
+        md.push_str(&format!("## {}\n\n", title));
+        for item in section.items {
+            markdown::format_item(&mut md, item, &module.path, src_prefix);
+        }
+        md.push('\n');
+    }
+
+    md
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn src_prefix_is_relative_to_the_chapters() {
+        assert_eq!(relative_src_prefix(Path::new("book/src/api"), Path::new("src")), "../../../src/");
+        assert_eq!(relative_src_prefix(Path::new("./book"), Path::new("./crate/../src")), "../src/");
+        assert_eq!(relative_src_prefix(Path::new("src/api"), Path::new("src")), "../");
+        assert_eq!(relative_src_prefix(Path::new("src"), Path::new("src")), "./");
+    }
+}
@@ -0,0 +1,212 @@
+use crate::doc_item::*;
+use crate::scope;
+use crate::trust;
+use crate::tree_sitter_wrapper;
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+///  A source file reached through a `mod` declaration, waiting to be parsed.
+struct PendingFile {
+    path: PathBuf,
+    module_path: String,
+    ///  Directory holding the files of this module's `mod foo;` children.
+    child_dir: PathBuf,
+    ///  Index of the `mod` declaration in the loaded items; None for the crate root.
+    decl: Option<usize>,
+}
+
+///  Everything read from a crate's source files.
+pub struct LoadedCrate {
+    pub items: Vec<RtDocItem>,
+    ///  Inner doc (`//!`) of the crate root, used as the document intro.
+    pub doc: Option<String>,
+}
+
+///  Find the crate root (`lib.rs`, else `main.rs`) in a source directory.
+pub fn find_crate_root(src_dir: &Path) -> Option<PathBuf> {
+    ["lib.rs", "main.rs"]
+        .iter()
+        .map(|name| src_dir.join(name))
+        .find(|path| path.is_file())
+}
+
+///  Extract items from every file reachable from `root_file` by following
+///  `mod` declarations. Item file paths are relative to `src_dir`; module
+///  paths are rooted at `crate`. Unreadable or missing files are reported
+///  as warnings and skipped. A module file's inner doc is appended to the
+///  doc comment of its `mod` declaration, and the trust of that declaration
+///  is added to every item in the file. Impl blocks are resolved to the
+///  crate types and traits they name once every file is loaded.
+pub fn load_crate(src_dir: &Path, root_file: &Path) -> LoadedCrate {
+    let mut all_items: Vec<RtDocItem> = Vec::new();
+    let mut crate_doc = None;
+    let mut seen: HashSet<PathBuf> = HashSet::new();
+    let mut pending = vec![PendingFile {
+        path: root_file.to_path_buf(),
+        module_path: "crate".to_string(),
+        child_dir: root_file.parent().unwrap_or(src_dir).to_path_buf(),
+        decl: None,
+    }];
+
+    while let Some(file) = pending.pop() {
+        //  `#[path]` attributes can point several declarations at one file
+        if !seen.insert(file.path.clone()) {
+            continue;
+        }
+
+        let source = match std::fs::read_to_string(&file.path) {
+            Ok(s) => s,
+            Err(e) => {
+                eprintln!("Warning: could not read {}: {}", file.path.display(), e);
+                continue;
+            }
+        };
+
+        let rel_path = file
+            .path
+            .strip_prefix(src_dir)
+            .unwrap_or(&file.path)
+            .to_string_lossy()
+            .to_string();
+
+        let mut items = match tree_sitter_wrapper::extract_items(&source, &rel_path, &file.module_path) {
+            Ok(items) => items,
+            Err(e) => {
+                eprintln!("Warning: parse error in {}: {}", file.path.display(), e);
+                continue;
+            }
+        };
+
+        let inner_doc = tree_sitter_wrapper::extract_inner_doc(&source);
+        match file.decl {
+            Some(index) => {
+                let decl = &mut all_items[index];
+                decl.doc_comment = tree_sitter_wrapper::join_docs(decl.doc_comment.take(), inner_doc);
+                //  `#[verifier::external] mod foo;` covers the whole file
+                trust::inherit(&mut items, &decl.trust);
+            }
+            None => crate_doc = inner_doc,
+        }
+
+        let first_index = all_items.len();
+        for (offset, decl) in items.iter().enumerate() {
+            if decl.item_kind != RtItemKind::Module || decl.has_body {
+                continue;
+            }
+            match resolve_mod_file(decl, &file, first_index + offset) {
+                Some(child) => pending.push(child),
+                None => eprintln!(
+                    "Warning: no file found for `mod {}` in {}:{}",
+                    decl.name, rel_path, decl.line_number
+                ),
+            }
+        }
+
+        all_items.extend(items);
+    }
+
+    scope::resolve_impls(&mut all_items);
+
+    LoadedCrate { items: all_items, doc: crate_doc }
+}
+
+///  Extract items from every `.rs` file under `src_dir`, for source trees
+///  without a crate root. Module paths come from file paths instead of `mod`
+///  declarations: `a/b.rs` and `a/b/mod.rs` are both `crate::a::b`.
+pub fn load_files(src_dir: &Path) -> LoadedCrate {
+    let mut files = Vec::new();
+    collect_rs_files(src_dir, &mut files);
+    files.sort();
+
+    let mut all_items: Vec<RtDocItem> = Vec::new();
+    for path in files {
+        let source = match std::fs::read_to_string(&path) {
+            Ok(s) => s,
+            Err(e) => {
+                eprintln!("Warning: could not read {}: {}", path.display(), e);
+                continue;
+            }
+        };
+
+        let rel = path.strip_prefix(src_dir).unwrap_or(&path).with_extension("");
+        let mut module_path = "crate".to_string();
+        for segment in rel.iter().map(|s| s.to_string_lossy()) {
+            if segment != "mod" {
+                module_path = format!("{}::{}", module_path, segment);
+            }
+        }
+        let rel_path = path.strip_prefix(src_dir).unwrap_or(&path).to_string_lossy().to_string();
+
+        match tree_sitter_wrapper::extract_items(&source, &rel_path, &module_path) {
+            Ok(items) => all_items.extend(items),
+            Err(e) => eprintln!("Warning: parse error in {}: {}", path.display(), e),
+        }
+    }
+
+    scope::resolve_impls(&mut all_items);
+
+    LoadedCrate { items: all_items, doc: None }
+}
+
+fn collect_rs_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_rs_files(&path, files);
+        } else if path.extension().is_some_and(|e| e == "rs") {
+            files.push(path);
+        }
+    }
+}
+
+///  Locate the file for an external `mod foo;` declaration made in `parent`.
+///  Follows rustc: `#[path]` is relative to the declaring file's directory
+///  (plus any enclosing inline modules), otherwise `foo.rs` or `foo/mod.rs`
+///  inside the parent's child directory. `decl_index` is the declaration's
+///  position in the loaded items.
+fn resolve_mod_file(decl: &RtDocItem, parent: &PendingFile, decl_index: usize) -> Option<PendingFile> {
+    let decl_index = Some(decl_index);
+    let module_path = format!("{}::{}", decl.module_path, decl.name);
+
+    //  Declarations inside inline `mod a { mod b; }` blocks look in `a/`
+    let mut dir = parent.child_dir.clone();
+    let mut path_attr_dir = parent.path.parent().map(Path::to_path_buf).unwrap_or_default();
+    if let Some(inline) = decl.module_path.strip_prefix(&parent.module_path) {
+        for segment in inline.split("::").filter(|s| !s.is_empty()) {
+            dir.push(segment);
+            path_attr_dir = dir.clone();
+        }
+    }
+
+    if let Some(path) = path_attribute(&decl.attributes) {
+        let path = path_attr_dir.join(path);
+        //  Files loaded through `#[path]` behave like `mod.rs` files
+        let child_dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        return path.is_file().then_some(PendingFile { path, module_path, child_dir, decl: decl_index });
+    }
+
+    let flat = dir.join(format!("{}.rs", decl.name));
+    if flat.is_file() {
+        return Some(PendingFile { path: flat, module_path, child_dir: dir.join(&decl.name), decl: decl_index });
+    }
+
+    let nested = dir.join(&decl.name).join("mod.rs");
+    if nested.is_file() {
+        return Some(PendingFile { path: nested, module_path, child_dir: dir.join(&decl.name), decl: decl_index });
+    }
+
+    None
+}
+
+///  Get the value of a `#[path = "..."]` attribute, if present.
+fn path_attribute(attributes: &[String]) -> Option<&str> {
+    attributes.iter().find_map(|attr| {
+        let rest = attr.strip_prefix("#[path")?.trim_start().strip_prefix('=')?;
+        let value = rest.trim().strip_suffix(']')?.trim();
+        value.strip_prefix('"')?.strip_suffix('"')
+    })
+}
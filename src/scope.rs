@@ -0,0 +1,146 @@
+use crate::doc_item::*;
+
+use std::collections::HashMap;
+
+///  How many `use` indirections are followed before giving up, so that
+///  cyclic imports cannot loop.
+const MAX_IMPORT_DEPTH: usize = 8;
+
+///  Declared items and `use` imports of a crate, for resolving paths written
+///  in a module the way rustc would.
+pub struct ScopeIndex {
+    ///  Kind and declared visibility of every item and module, by full path.
+    items: HashMap<String, (RtItemKind, RtVisibility)>,
+    ///  `(name, path as written)` pairs imported into each module; glob
+    ///  imports have the name `*`.
+    imports: HashMap<String, Vec<(String, String)>>,
+}
+
+impl ScopeIndex {
+    pub fn new(items: &[RtDocItem]) -> Self {
+        let mut index = ScopeIndex { items: HashMap::new(), imports: HashMap::new() };
+        for item in items {
+            match item.item_kind {
+                RtItemKind::Use => {
+                    if let Some(ref path) = item.reexport_of {
+                        let imports = index.imports.entry(item.module_path.clone()).or_default();
+                        imports.push((item.name.clone(), path.clone()));
+                    }
+                }
+                RtItemKind::Impl => {}
+                kind => {
+                    let path = format!("{}::{}", item.module_path, item.name);
+                    index.items.entry(path).or_insert((kind, item.visibility.clone()));
+                }
+            }
+        }
+        index
+    }
+
+    ///  Kind of the item declared at a full path.
+    pub fn kind(&self, path: &str) -> Option<RtItemKind> {
+        self.items.get(path).map(|(kind, _)| *kind)
+    }
+
+    ///  Declared visibility of the item at a full path.
+    pub fn visibility(&self, path: &str) -> Option<&RtVisibility> {
+        self.items.get(path).map(|(_, visibility)| visibility)
+    }
+
+    ///  Resolve a path written in `module`, e.g. `View<T>` or `super::a::Foo`,
+    ///  to the full path of a crate item. Generic arguments are ignored.
+    ///  Returns None for paths into other crates and names that are not declared.
+    pub fn resolve(&self, module: &str, path: &str) -> Option<String> {
+        self.resolve_path(module, path).filter(|resolved| self.items.contains_key(resolved))
+    }
+
+    ///  Resolve a path like `resolve`, without requiring what it names to be
+    ///  declared: `use` paths may name another re-export or end in a glob
+    ///  (`a::*`). Only the first segment has to be in scope.
+    pub fn resolve_path(&self, module: &str, path: &str) -> Option<String> {
+        self.resolve_at_depth(module, &strip_generics(path), 0)
+    }
+
+    fn resolve_at_depth(&self, module: &str, path: &str, depth: usize) -> Option<String> {
+        if depth > MAX_IMPORT_DEPTH {
+            return None;
+        }
+        let mut segments = path.split("::").map(str::trim);
+        let mut resolved = match segments.next()? {
+            "crate" => "crate".to_string(),
+            "self" => module.to_string(),
+            "super" => module.rsplit_once("::")?.0.to_string(),
+            name => self.resolve_name(module, name, depth)?,
+        };
+        for segment in segments {
+            if segment == "super" {
+                resolved = resolved.rsplit_once("::")?.0.to_string();
+            } else {
+                resolved = format!("{}::{}", resolved, segment);
+            }
+        }
+        Some(resolved)
+    }
+
+    ///  Resolve the first segment of a path: an item declared in `module`,
+    ///  then a name it imports, then a name brought in by a glob import.
+    fn resolve_name(&self, module: &str, name: &str, depth: usize) -> Option<String> {
+        let local = format!("{}::{}", module, name);
+        if self.items.contains_key(&local) {
+            return Some(local);
+        }
+        let imports = self.imports.get(module)?;
+        if let Some((_, path)) = imports.iter().find(|(alias, _)| alias == name) {
+            return self.resolve_at_depth(module, path, depth + 1);
+        }
+        imports
+            .iter()
+            .filter(|(alias, _)| alias == "*")
+            .filter_map(|(_, path)| self.resolve_at_depth(module, path.trim_end_matches("::*"), depth + 1))
+            .map(|source| format!("{}::{}", source, name))
+            .find(|candidate| self.items.contains_key(candidate))
+    }
+}
+
+///  Remove generic arguments from a path: `a::Foo<T>` becomes `a::Foo`.
+fn strip_generics(path: &str) -> String {
+    let mut out = String::new();
+    let mut depth = 0;
+    for c in path.chars() {
+        match c {
+            '<' => depth += 1,
+            '>' => depth -= 1,
+            _ if depth == 0 => out.push(c),
+            _ => {}
+        }
+    }
+    out.trim().to_string()
+}
+
+///  Resolve the self type and trait of every impl block to the crate items
+///  they name (`self_target`, `trait_target`). Members of a trait impl take
+///  the visibility of the trait when it is defined in the crate; impls of
+///  external traits stay public.
+pub fn resolve_impls(items: &mut [RtDocItem]) {
+    let index = ScopeIndex::new(items);
+    for item in items.iter_mut() {
+        let Some(block) = item.impl_block.as_mut() else {
+            continue;
+        };
+        block.self_target = index
+            .resolve(&block.module_path, &block.self_ty)
+            .filter(|path| index.kind(path).is_some_and(|kind| kind.is_type()));
+        block.trait_target = block
+            .trait_path
+            .as_deref()
+            .and_then(|path| index.resolve(&block.module_path, path))
+            .filter(|path| index.kind(path) == Some(RtItemKind::Trait));
+
+        if block.trait_path.is_some() && item.item_kind != RtItemKind::Impl {
+            item.visibility = match block.trait_target {
+                Some(ref path) => index.visibility(path).cloned().unwrap_or(RtVisibility::Public),
+                None => RtVisibility::Public,
+            };
+        }
+    }
+}
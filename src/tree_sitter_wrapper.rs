@@ -1,4 +1,6 @@
+use crate::contract;
 use crate::doc_item::*;
+use crate::trust;
 
 ///  Extract documentation items from a Verus source file using tree-sitter.
 pub fn extract_items(
@@ -19,10 +21,29 @@ pub fn extract_items(
     let mut items = Vec::new();
 
     collect_items_from_node(&root, source, file_path, module_path, &mut items);
+    //  A file-level `#![verifier::external]` covers everything in the file
+    let file_trust = trust::attribute_sites(&extract_inner_attributes(&root, source), 1);
+    trust::inherit(&mut items, &file_trust);
+    add_attribute_trust(&mut items);
 
     Ok(items)
 }
 
+///  Record the trust implied by verifier attributes on items and their members,
+///  ahead of any sites found in their bodies or inherited from containers.
+fn add_attribute_trust(items: &mut [RtDocItem]) {
+    for item in items {
+        let mut sites = trust::attribute_trust(item);
+        for site in item.trust.drain(..) {
+            if !sites.iter().any(|s| s.kind == site.kind && s.text == site.text) {
+                sites.push(site);
+            }
+        }
+        item.trust = sites;
+        add_attribute_trust(&mut item.members);
+    }
+}
+
 ///  Recursively collect documentation items from a tree-sitter node.
 fn collect_items_from_node(
     node: &tree_sitter::Node,
@@ -48,6 +69,54 @@ fn collect_items_from_node(
                     items.push(item);
                 }
             }
+            "struct_item" | "enum_item" => {
+                if let Some(item) = extract_type_item(&child, source, file_path, module_path) {
+                    items.push(item);
+                }
+            }
+            "type_item" => {
+                if let Some(item) = extract_type_alias(&child, source, file_path, module_path) {
+                    items.push(item);
+                }
+            }
+            "const_item" | "static_item" => {
+                if let Some(item) = extract_const_item(&child, source, file_path, module_path) {
+                    items.push(item);
+                }
+            }
+            "trait_item" => {
+                if let Some(item) = extract_trait_item(&child, source, file_path, module_path) {
+                    items.push(item);
+                }
+            }
+            "use_declaration" => {
+                extract_use_items(&child, source, file_path, module_path, items);
+            }
+            "mod_item" => {
+                if let Some(mut item) = extract_mod_item(&child, source, file_path, module_path) {
+                    //  Inline `mod foo { ... }` blocks are collected here; external
+                    //  `mod foo;` files are loaded by `module_tree`
+                    if let Some(body) = child.child_by_field_name("body") {
+                        let inner_path = format!("{}::{}", module_path, item.name);
+                        let start = items.len();
+                        collect_items_from_node(&body, source, file_path, &inner_path, items);
+                        item.attributes.extend(extract_inner_attributes(&body, source));
+                        let sites = trust::attribute_trust(&item);
+                        trust::inherit(&mut items[start..], &sites);
+                    }
+                    items.push(item);
+                }
+            }
+            _ if is_broadcast_group(&child, source) => {
+                if let Some(item) = extract_broadcast_group(&child, source, file_path, module_path) {
+                    items.push(item);
+                }
+            }
+            _ if is_assume_specification(&child, source) => {
+                if let Some(item) = extract_assume_specification(&child, source, file_path, module_path) {
+                    items.push(item);
+                }
+            }
             _ => {
                 //  Don't recurse into other node types (token_trees etc.)
             }
@@ -55,6 +124,134 @@ fn collect_items_from_node(
     }
 }
 
+///  Whether `node` is an `assume_specification[path](..)` declaration, recognized
+///  by node kind or, where the grammar has no dedicated node, by its text.
+fn is_assume_specification(node: &tree_sitter::Node, source: &str) -> bool {
+    if node.kind().contains("assume_specification") {
+        return true;
+    }
+    skip_visibility(&node_text(node, source)).starts_with("assume_specification")
+}
+
+///  `text` after any leading visibility such as `pub` or `pub(crate)`.
+fn skip_visibility(text: &str) -> &str {
+    let text = text.trim_start();
+    match text.strip_prefix("pub") {
+        Some(rest) => match rest.trim_start().strip_prefix('(') {
+            Some(restricted) => restricted.split_once(')').map_or("", |(_, after)| after.trim_start()),
+            None => rest.trim_start(),
+        },
+        None => text,
+    }
+}
+
+///  Whether `node` is a `broadcast group name { .. }` declaration, recognized
+///  by node kind or, where the grammar has no dedicated node, by its text.
+fn is_broadcast_group(node: &tree_sitter::Node, source: &str) -> bool {
+    node.kind().contains("broadcast_group")
+        || skip_visibility(&node_text(node, source))
+            .strip_prefix("broadcast")
+            .is_some_and(|rest| rest.trim_start().starts_with("group"))
+}
+
+///  Extract a broadcast group with the paths of its members as written.
+fn extract_broadcast_group(
+    node: &tree_sitter::Node,
+    source: &str,
+    file_path: &str,
+    module_path: &str,
+) -> Option<RtDocItem> {
+    let text = contract::strip_comments(&node_text(node, source));
+    let (name, members) = broadcast_group_parts(&text)?;
+
+    let group_members = contract::split_top_level_commas(members)
+        .into_iter()
+        .map(|member| contract::collapse_whitespace(&member))
+        .filter(|member| !member.is_empty())
+        .map(|member| RtXref { name: member.clone(), path: member, url: String::new() })
+        .collect();
+
+    Some(RtDocItem {
+        name: name.to_string(),
+        visibility: extract_visibility(node, source),
+        line_number: node.start_position().row + 1,
+        end_line_number: node.end_position().row + 1,
+        file_path: file_path.to_string(),
+        doc_comment: extract_doc_comment(node, source),
+        module_path: module_path.to_string(),
+        signature: contract::collapse_whitespace(&text[..text.find('{')?]),
+        item_kind: RtItemKind::BroadcastGroup,
+        attributes: extract_attributes(node, source),
+        group_members,
+        ..Default::default()
+    })
+}
+
+///  The name and the text between the braces of `[pub] broadcast group name { .. }`.
+///  The `broadcast` and `group` tokens are stripped after the visibility, so
+///  a `group` in a `pub(in ..)` path or in the name is not taken for them.
+fn broadcast_group_parts(text: &str) -> Option<(&str, &str)> {
+    let rest = skip_visibility(text).strip_prefix("broadcast")?.trim_start().strip_prefix("group")?;
+    if !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let open = rest.find('{')?;
+    let close = rest.rfind('}')?;
+    let name = rest[..open].trim();
+    if name.is_empty() || close < open {
+        return None;
+    }
+    Some((name, &rest[open + 1..close]))
+}
+
+///  Extract an `assume_specification` as a function item named by the
+///  specified path, whose contract is trusted.
+fn extract_assume_specification(
+    node: &tree_sitter::Node,
+    source: &str,
+    file_path: &str,
+    module_path: &str,
+) -> Option<RtDocItem> {
+    let text = node_text(node, source);
+    let start = text.find("assume_specification")?;
+    let rest = &text[start + "assume_specification".len()..];
+    //  The path may carry generics: `assume_specification<T>[ Vec::<T>::len ]`
+    let open = rest.find('[')?;
+    let close = open + rest[open..].find(']')?;
+    let path = contract::collapse_whitespace(&rest[open + 1..close]);
+    let name = path.rsplit("::").next().unwrap_or(&path).trim().to_string();
+    if name.is_empty() {
+        return None;
+    }
+
+    //  The header runs up to the body (if any) or the trailing `;`
+    let header = match node.child_by_field_name("body") {
+        Some(body) => &source[node.start_byte()..body.start_byte()],
+        None => text.trim_end().trim_end_matches(';'),
+    };
+    let line_number = node.start_position().row + 1;
+
+    Some(RtDocItem {
+        name,
+        kind: RtFnKind::Exec,
+        visibility: extract_visibility(node, source),
+        line_number,
+        end_line_number: node.end_position().row + 1,
+        file_path: file_path.to_string(),
+        doc_comment: extract_doc_comment(node, source),
+        module_path: module_path.to_string(),
+        signature: contract::parse_signature(header),
+        contract: contract::parse_contract(header),
+        attributes: extract_attributes(node, source),
+        trust: vec![RtTrustSite {
+            kind: RtTrustKind::AssumeSpecification,
+            line_number,
+            text: format!("assume_specification[{}]", path),
+        }],
+        ..Default::default()
+    })
+}
+
 ///  Collect function items from inside an impl block.
 fn collect_items_from_impl(
     impl_node: &tree_sitter::Node,
@@ -66,24 +263,84 @@ fn collect_items_from_impl(
     //  Get the type name for context
     let type_name = impl_node
         .child_by_field_name("type")
-        .map(|n| node_text(&n, source))
+        .map(|n| contract::normalize_code(&node_text(&n, source)))
         .unwrap_or_default();
 
-    let impl_module = if type_name.is_empty() {
+    //  Methods of `impl Foo<T>` and `impl View for Foo<T>` share the `module::Foo`
+    //  pseudo-module; the impl header is kept on each item to tell them apart.
+    let base_name = type_name.split('<').next().unwrap_or("").trim();
+    let impl_module = if base_name.is_empty() {
         module_path.to_string()
     } else {
-        format!("{}::{}", module_path, type_name)
+        format!("{}::{}", module_path, base_name)
+    };
+
+    let impl_block = RtImplBlock {
+        self_ty: type_name.clone(),
+        trait_path: impl_node
+            .child_by_field_name("trait")
+            .map(|n| contract::normalize_code(&node_text(&n, source))),
+        generics: impl_node
+            .child_by_field_name("type_parameters")
+            .map(|n| contract::normalize_code(&node_text(&n, source))),
+        module_path: module_path.to_string(),
+        ..Default::default()
     };
+    let attributes = extract_attributes(impl_node, source);
+
+    //  Record trait impls so they can be listed under their trait
+    if impl_block.trait_path.is_some() {
+        let header_end = impl_node
+            .child_by_field_name("body")
+            .map_or(impl_node.end_byte(), |b| b.start_byte());
+        items.push(RtDocItem {
+            name: type_name.clone(),
+            //  impl blocks have no visibility of their own
+            visibility: RtVisibility::Public,
+            line_number: impl_node.start_position().row + 1,
+            end_line_number: impl_node.end_position().row + 1,
+            file_path: file_path.to_string(),
+            module_path: module_path.to_string(),
+            signature: contract::normalize_code(&source[impl_node.start_byte()..header_end]),
+            item_kind: RtItemKind::Impl,
+            impl_block: Some(impl_block.clone()),
+            attributes: attributes.clone(),
+            ..Default::default()
+        });
+    }
 
     //  Find the declaration_list (body)
+    let start = items.len();
     if let Some(body) = impl_node.child_by_field_name("body") {
         let mut cursor = body.walk();
         for child in body.children(&mut cursor) {
             match child.kind() {
                 "function_item" | "function_signature_item" => {
-                    if let Some(item) =
+                    if let Some(mut item) =
                         extract_function_item(&child, source, file_path, &impl_module)
                     {
+                        //  Trait impl methods are as visible as the trait itself, which
+                        //  `scope::resolve_impls` looks up once the crate is loaded
+                        if impl_block.trait_path.is_some() {
+                            item.visibility = RtVisibility::Public;
+                        } else {
+                            item.invariant_kind = invariant_kind(&item, &child, source);
+                        }
+                        //  A type invariant is shown even when closed: it holds for every value
+                        if item.invariant_kind == Some(RtInvariantKind::TypeInvariant) && item.spec_body.is_none() {
+                            item.spec_body = child.child_by_field_name("body").map(|b| block_contents(&b, source));
+                        }
+                        item.impl_block = Some(impl_block.clone());
+                        items.push(item);
+                    }
+                }
+                "type_item" => {
+                    //  `type V = T;` in a trait impl, shown with the impl's methods
+                    if let Some(mut item) = extract_type_alias(&child, source, file_path, &impl_module) {
+                        item.item_kind = RtItemKind::AssocType;
+                        //  As visible as the trait, like the impl's methods
+                        item.visibility = RtVisibility::Public;
+                        item.impl_block = Some(impl_block.clone());
                         items.push(item);
                     }
                 }
@@ -91,6 +348,56 @@ fn collect_items_from_impl(
             }
         }
     }
+
+    //  `#[verifier::external_body]` etc. on the impl covers each of its members
+    let sites = trust::attribute_sites(&attributes, impl_node.start_position().row + 1);
+    trust::inherit(&mut items[start..], &sites);
+}
+
+///  Whether a method of an inherent impl states an invariant of its type:
+///  a `#[verifier::type_invariant]` function, or a spec predicate on `self`
+///  conventionally named `wf` or `inv`.
+fn invariant_kind(item: &RtDocItem, node: &tree_sitter::Node, source: &str) -> Option<RtInvariantKind> {
+    let is_type_invariant = item
+        .attributes
+        .iter()
+        .any(|attr| trust::verifier_attribute_name(attr) == Some("type_invariant"));
+    if is_type_invariant {
+        return Some(RtInvariantKind::TypeInvariant);
+    }
+
+    let is_predicate = item.kind == RtFnKind::Spec
+        && has_self_parameter(node)
+        && returns_bool(node, source, &item.signature);
+    let is_conventional = matches!(item.name.as_str(), "wf" | "inv" | "well_formed" | "invariant");
+    (is_predicate && is_conventional).then_some(RtInvariantKind::WellFormed)
+}
+
+///  Whether a function takes a `self` receiver, as opposed to merely
+///  mentioning `self` in a parameter name or its contract.
+fn has_self_parameter(node: &tree_sitter::Node) -> bool {
+    let Some(parameters) = node.child_by_field_name("parameters") else {
+        return false;
+    };
+    let mut cursor = parameters.walk();
+    let found = parameters.children(&mut cursor).any(|p| p.kind() == "self_parameter");
+    found
+}
+
+///  Whether a function returns `bool`, either plainly or as a named return
+///  `-> (b: bool)`. Falls back to the signature text when the grammar has no
+///  return type field.
+fn returns_bool(node: &tree_sitter::Node, source: &str, signature: &str) -> bool {
+    let ret = match node.child_by_field_name("return_type") {
+        Some(ty) => node_text(&ty, source),
+        None => match signature.rsplit_once("->") {
+            Some((_, ret)) => ret.to_string(),
+            None => return false,
+        },
+    };
+    let ret = ret.trim();
+    let ret = ret.strip_prefix('(').and_then(|r| r.strip_suffix(')')).unwrap_or(ret);
+    ret.rsplit(':').next().unwrap_or(ret).trim() == "bool"
 }
 
 ///  Extract a single function item from a function_item or function_signature_item node.
@@ -107,7 +414,7 @@ fn extract_function_item(
     let visibility = extract_visibility(node, source);
 
     //  Determine function kind and open/closed from function_modifiers
-    let (kind, is_open) = extract_fn_kind(node, source);
+    let (kind, is_open, is_broadcast) = extract_fn_kind(node, source);
 
     //  Get line number (1-based)
     let line_number = node.start_position().row + 1;
@@ -115,84 +422,681 @@ fn extract_function_item(
     //  Extract doc comment from preceding sibling
     let doc_comment = extract_doc_comment(node, source);
 
+    //  Parse the signature and requires/ensures/etc. from the header text before the body
+    let header = function_header(node, source);
+    let signature = contract::parse_signature(&header);
+    let contract = contract::parse_contract(&header);
+
     Some(RtDocItem {
         name: name_text,
         kind,
         visibility,
         is_open,
+        is_broadcast,
         line_number,
+        end_line_number: node.end_position().row + 1,
         file_path: file_path.to_string(),
         doc_comment,
         module_path: module_path.to_string(),
+        signature,
+        contract,
+        has_body: node.child_by_field_name("body").is_some(),
+        attributes: extract_attributes(node, source),
+        spec_body: node
+            .child_by_field_name("body")
+            .filter(|_| kind == RtFnKind::Spec && is_open)
+            .map(|b| block_contents(&b, source)),
+        body_calls: node.child_by_field_name("body").map_or(Vec::new(), |b| extract_calls(&b, source)),
+        trust: node.child_by_field_name("body").map_or(Vec::new(), |b| extract_body_trust(&b, source)),
+        ..Default::default()
+    })
+}
+
+///  Collect the functions called anywhere under `node`, in source order and
+///  without repeats: `f(..)`, `a::f(..)` and `f::<T>(..)` as the path written,
+///  and `self.f(..)` as `Self::f`. Other method calls are skipped, since
+///  their receiver type is unknown.
+fn extract_calls(node: &tree_sitter::Node, source: &str) -> Vec<String> {
+    let mut calls = Vec::new();
+    let mut stack = vec![*node];
+    while let Some(current) = stack.pop() {
+        if current.kind() == "call_expression" {
+            if let Some(path) = current.child_by_field_name("function").and_then(|f| call_path(&f, source)) {
+                if !calls.contains(&path) {
+                    calls.push(path);
+                }
+            }
+        }
+        //  Push children in reverse so they are visited in source order
+        let mut cursor = current.walk();
+        let children: Vec<_> = current.children(&mut cursor).collect();
+        stack.extend(children.into_iter().rev());
+    }
+    calls
+}
+
+///  Collect the `assume(..)` and `admit()` sites under `node`, in source order.
+///  Both are recognized as calls or, where the grammar has dedicated nodes for
+///  them, by node kind.
+fn extract_body_trust(node: &tree_sitter::Node, source: &str) -> Vec<RtTrustSite> {
+    let mut sites = Vec::new();
+    let mut stack = vec![*node];
+    while let Some(current) = stack.pop() {
+        let name = match current.kind() {
+            "call_expression" => current
+                .child_by_field_name("function")
+                .and_then(|f| call_path(&f, source))
+                .map(|path| path.rsplit("::").next().unwrap_or("").to_string()),
+            kind => Some(kind.to_string()),
+        };
+        let kind = match name.as_deref() {
+            Some("assume") | Some("assume_expression") => Some(RtTrustKind::Assume),
+            Some("admit") | Some("admit_expression") => Some(RtTrustKind::Admit),
+            _ => None,
+        };
+        if let Some(kind) = kind {
+            sites.push(RtTrustSite {
+                kind,
+                line_number: current.start_position().row + 1,
+                text: truncate(&contract::collapse_whitespace(&node_text(&current, source)), 80),
+            });
+            continue;
+        }
+        //  Push children in reverse so they are visited in source order
+        let mut cursor = current.walk();
+        let children: Vec<_> = current.children(&mut cursor).collect();
+        stack.extend(children.into_iter().rev());
+    }
+    sites
+}
+
+///  Shorten `text` to at most `max` characters, marking the cut with `…`.
+fn truncate(text: &str, max: usize) -> String {
+    match text.char_indices().nth(max) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+///  The path of a call's function expression, if it names a function.
+fn call_path(function: &tree_sitter::Node, source: &str) -> Option<String> {
+    match function.kind() {
+        "identifier" | "scoped_identifier" => Some(contract::collapse_whitespace(&node_text(function, source))),
+        "generic_function" => call_path(&function.child_by_field_name("function")?, source),
+        "field_expression" => {
+            let receiver = function.child_by_field_name("value")?;
+            let field = function.child_by_field_name("field")?;
+            (node_text(&receiver, source) == "self").then(|| format!("Self::{}", node_text(&field, source)))
+        }
+        _ => None,
+    }
+}
+
+///  Extract a trait with its associated types and required/provided methods.
+///  Members inherit the trait's visibility and live under `module::Trait`.
+fn extract_trait_item(
+    node: &tree_sitter::Node,
+    source: &str,
+    file_path: &str,
+    module_path: &str,
+) -> Option<RtDocItem> {
+    let name = node.child_by_field_name("name")?;
+    let name_text = node_text(&name, source);
+    let visibility = extract_visibility(node, source);
+    let member_module = format!("{}::{}", module_path, name_text);
+
+    let body = node.child_by_field_name("body");
+    let header_end = body.map_or(node.end_byte(), |b| b.start_byte());
+
+    let mut members = Vec::new();
+    if let Some(body) = body {
+        let mut cursor = body.walk();
+        for child in body.children(&mut cursor) {
+            let member = match child.kind() {
+                "function_item" | "function_signature_item" => {
+                    extract_function_item(&child, source, file_path, &member_module)
+                }
+                "associated_type" => {
+                    extract_associated_type(&child, source, file_path, &member_module)
+                }
+                _ => None,
+            };
+            if let Some(mut member) = member {
+                member.visibility = visibility.clone();
+                members.push(member);
+            }
+        }
+    }
+
+    Some(RtDocItem {
+        name: name_text,
+        visibility,
+        line_number: node.start_position().row + 1,
+        end_line_number: node.end_position().row + 1,
+        file_path: file_path.to_string(),
+        doc_comment: extract_doc_comment(node, source),
+        module_path: module_path.to_string(),
+        signature: contract::parse_signature(&source[node.start_byte()..header_end]),
+        item_kind: RtItemKind::Trait,
+        members,
+        attributes: extract_attributes(node, source),
+        ..Default::default()
+    })
+}
+
+///  Extract a `mod foo;` or `mod foo { ... }` declaration (without its contents).
+///  The doc comment includes the inner docs of an inline body; those of a
+///  module file are added when the file is loaded.
+fn extract_mod_item(
+    node: &tree_sitter::Node,
+    source: &str,
+    file_path: &str,
+    module_path: &str,
+) -> Option<RtDocItem> {
+    let name = node.child_by_field_name("name")?;
+    let body = node.child_by_field_name("body");
+    let header_end = body.map_or(node.end_byte(), |b| b.start_byte());
+
+    Some(RtDocItem {
+        name: node_text(&name, source),
+        visibility: extract_visibility(node, source),
+        line_number: node.start_position().row + 1,
+        end_line_number: node.end_position().row + 1,
+        file_path: file_path.to_string(),
+        doc_comment: join_docs(
+            extract_doc_comment(node, source),
+            body.and_then(|b| extract_inner_doc(node_text(&b, source).trim_start_matches('{'))),
+        ),
+        module_path: module_path.to_string(),
+        signature: contract::normalize_code(source[node.start_byte()..header_end].trim_end_matches(';')),
+        item_kind: RtItemKind::Module,
+        has_body: body.is_some(),
+        attributes: extract_attributes(node, source),
+        ..Default::default()
+    })
+}
+
+///  Extract one `Use` item per name imported by a non-private `use` declaration,
+///  e.g. `pub use a::{b, c as d};` yields `b` (from `a::b`) and `d` (from `a::c`).
+fn extract_use_items(
+    node: &tree_sitter::Node,
+    source: &str,
+    file_path: &str,
+    module_path: &str,
+    items: &mut Vec<RtDocItem>,
+) {
+    //  Private imports are kept for resolving paths; only `pub use` re-exports
+    let visibility = extract_visibility(node, source);
+    let Some(argument) = node.child_by_field_name("argument") else {
+        return;
+    };
+
+    let mut imports = Vec::new();
+    expand_use_tree("", &node_text(&argument, source), &mut imports);
+
+    for (path, name) in imports {
+        items.push(RtDocItem {
+            name,
+            visibility: visibility.clone(),
+            line_number: node.start_position().row + 1,
+            end_line_number: node.end_position().row + 1,
+            file_path: file_path.to_string(),
+            module_path: module_path.to_string(),
+            signature: contract::normalize_code(node_text(node, source).trim_end_matches(';')),
+            item_kind: RtItemKind::Use,
+            reexport_of: Some(path),
+            ..Default::default()
+        });
+    }
+}
+
+///  Flatten a use tree into (path, imported name) pairs. Glob imports keep
+///  `*` as both the last path segment and the name; `_` imports are dropped.
+fn expand_use_tree(prefix: &str, tree: &str, out: &mut Vec<(String, String)>) {
+    let tree = contract::collapse_whitespace(tree);
+    let join = |rest: &str| {
+        if prefix.is_empty() || rest.is_empty() {
+            format!("{}{}", prefix, rest)
+        } else {
+            format!("{}::{}", prefix, rest)
+        }
+    };
+
+    if let (Some(open), true) = (tree.find('{'), tree.ends_with('}')) {
+        let head = join(tree[..open].trim().trim_end_matches("::"));
+        for part in contract::split_top_level_commas(&tree[open + 1..tree.len() - 1]) {
+            expand_use_tree(&head, &part, out);
+        }
+    } else if let Some((path, alias)) = tree.split_once(" as ") {
+        if alias.trim() != "_" {
+            out.push((join(path.trim()), alias.trim().to_string()));
+        }
+    } else if tree == "self" {
+        let name = prefix.rsplit("::").next().unwrap_or(prefix);
+        out.push((prefix.to_string(), name.to_string()));
+    } else {
+        let name = tree.rsplit("::").next().unwrap_or(&tree);
+        out.push((join(&tree), name.to_string()));
+    }
+}
+
+///  Extract an associated type declaration (`type V: Bound;`) from a trait body.
+fn extract_associated_type(
+    node: &tree_sitter::Node,
+    source: &str,
+    file_path: &str,
+    module_path: &str,
+) -> Option<RtDocItem> {
+    let name = node.child_by_field_name("name")?;
+    let text = node_text(node, source);
+
+    Some(RtDocItem {
+        name: node_text(&name, source),
+        line_number: node.start_position().row + 1,
+        end_line_number: node.end_position().row + 1,
+        file_path: file_path.to_string(),
+        doc_comment: extract_doc_comment(node, source),
+        module_path: module_path.to_string(),
+        signature: contract::normalize_code(text.trim_end_matches(';')),
+        item_kind: RtItemKind::AssocType,
+        ..Default::default()
+    })
+}
+
+///  Extract a `type Name<T> = Target;` alias. The signature stops before `=`;
+///  the target type is kept separately so it can be linked.
+fn extract_type_alias(
+    node: &tree_sitter::Node,
+    source: &str,
+    file_path: &str,
+    module_path: &str,
+) -> Option<RtDocItem> {
+    let name = node.child_by_field_name("name")?;
+    let target = node.child_by_field_name("type")?;
+    let header = source[node.start_byte()..target.start_byte()].trim_end().trim_end_matches('=');
+
+    Some(RtDocItem {
+        name: node_text(&name, source),
+        visibility: extract_visibility(node, source),
+        line_number: node.start_position().row + 1,
+        end_line_number: node.end_position().row + 1,
+        file_path: file_path.to_string(),
+        doc_comment: extract_doc_comment(node, source),
+        module_path: module_path.to_string(),
+        signature: contract::normalize_code(header),
+        aliased_type: Some(contract::normalize_code(&node_text(&target, source))),
+        item_kind: RtItemKind::TypeAlias,
+        attributes: extract_attributes(node, source),
+        ..Default::default()
+    })
+}
+
+///  Extract a struct or enum together with its fields or variants.
+fn extract_type_item(
+    node: &tree_sitter::Node,
+    source: &str,
+    file_path: &str,
+    module_path: &str,
+) -> Option<RtDocItem> {
+    let name = node.child_by_field_name("name")?;
+    let item_kind = if node.kind() == "enum_item" {
+        RtItemKind::Enum
+    } else {
+        RtItemKind::Struct
+    };
+
+    //  Named-field bodies and variant lists are rendered separately; tuple
+    //  struct fields stay in the signature like rustdoc shows them.
+    let body = node.child_by_field_name("body");
+    let header_end = match body {
+        Some(b) if b.kind() != "ordered_field_declaration_list" => b.start_byte(),
+        _ => node.end_byte(),
+    };
+    let header = source[node.start_byte()..header_end].trim_end().trim_end_matches(';');
+
+    let attributes = extract_attributes(node, source);
+    let mut fields = Vec::new();
+    let mut variants = Vec::new();
+    if let Some(body) = body {
+        match body.kind() {
+            "field_declaration_list" => fields = extract_named_fields(&body, source),
+            "ordered_field_declaration_list" => fields = extract_ordered_fields(&body, source),
+            "enum_variant_list" => variants = extract_variants(&body, source),
+            _ => {}
+        }
+    }
+
+    Some(RtDocItem {
+        name: node_text(&name, source),
+        visibility: extract_visibility(node, source),
+        line_number: node.start_position().row + 1,
+        end_line_number: node.end_position().row + 1,
+        file_path: file_path.to_string(),
+        doc_comment: extract_doc_comment(node, source),
+        module_path: module_path.to_string(),
+        signature: contract::parse_signature(header),
+        item_kind,
+        derives: extract_derives(&attributes),
+        attributes,
+        fields,
+        variants,
+        ..Default::default()
     })
 }
 
+///  Extract a `const` or `static` with its mode. The value is kept for
+///  `spec` and `open` consts, whose value is part of the specification.
+fn extract_const_item(
+    node: &tree_sitter::Node,
+    source: &str,
+    file_path: &str,
+    module_path: &str,
+) -> Option<RtDocItem> {
+    let name = node.child_by_field_name("name")?;
+    let item_kind = if node.kind() == "static_item" {
+        RtItemKind::Static
+    } else {
+        RtItemKind::Const
+    };
+
+    //  Modes may be parsed as modifiers or bare tokens; read them from the text
+    let (kind, is_open) = const_mode(&source[node.start_byte()..name.start_byte()]);
+
+    let value = node.child_by_field_name("value");
+    let header = match value {
+        Some(v) => source[node.start_byte()..v.start_byte()].trim_end().trim_end_matches('='),
+        None => source[node.start_byte()..node.end_byte()].trim_end().trim_end_matches(';'),
+    };
+    let shows_value = item_kind == RtItemKind::Const && (kind == RtFnKind::Spec || is_open);
+
+    Some(RtDocItem {
+        name: node_text(&name, source),
+        kind,
+        visibility: extract_visibility(node, source),
+        is_open,
+        line_number: node.start_position().row + 1,
+        end_line_number: node.end_position().row + 1,
+        file_path: file_path.to_string(),
+        doc_comment: extract_doc_comment(node, source),
+        module_path: module_path.to_string(),
+        signature: contract::normalize_code(header),
+        value: value.filter(|_| shows_value).map(|v| contract::normalize_code(&node_text(&v, source))),
+        item_kind,
+        attributes: extract_attributes(node, source),
+        ..Default::default()
+    })
+}
+
+///  The mode of a const or static from the words before its name, and
+///  whether it is `open`. Without a mode it is `exec`.
+fn const_mode(header: &str) -> (RtFnKind, bool) {
+    let mut kind = RtFnKind::Exec;
+    let mut is_open = false;
+    for word in header.split_whitespace() {
+        match word {
+            "spec" | "ghost" => kind = RtFnKind::Spec,
+            "proof" | "tracked" => kind = RtFnKind::Proof,
+            "exec" => kind = RtFnKind::Exec,
+            "open" => is_open = true,
+            _ => {}
+        }
+    }
+    (kind, is_open)
+}
+
+///  Extract `name: Type` fields from a field_declaration_list.
+fn extract_named_fields(body: &tree_sitter::Node, source: &str) -> Vec<RtField> {
+    let mut fields = Vec::new();
+    let mut cursor = body.walk();
+    for child in body.children(&mut cursor) {
+        if child.kind() != "field_declaration" {
+            continue;
+        }
+        let (Some(name), Some(ty)) = (
+            child.child_by_field_name("name"),
+            child.child_by_field_name("type"),
+        ) else {
+            continue;
+        };
+        fields.push(RtField {
+            name: node_text(&name, source),
+            ty: contract::normalize_code(&node_text(&ty, source)),
+            visibility: extract_visibility(&child, source),
+            line_number: child.start_position().row + 1,
+            doc_comment: extract_doc_comment(&child, source),
+        });
+    }
+    fields
+}
+
+///  Extract positional fields from an ordered_field_declaration_list,
+///  naming them `0`, `1`, ... as in field access syntax.
+fn extract_ordered_fields(body: &tree_sitter::Node, source: &str) -> Vec<RtField> {
+    let mut fields = Vec::new();
+    let mut visibility = RtVisibility::Private;
+    let mut cursor = body.walk();
+    for child in body.children(&mut cursor) {
+        match child.kind() {
+            "(" | ")" | "," | "attribute_item" | "line_comment" | "block_comment" => {}
+            "visibility_modifier" => {
+                visibility = parse_visibility_modifier(&node_text(&child, source));
+            }
+            _ => {
+                fields.push(RtField {
+                    name: fields.len().to_string(),
+                    ty: contract::normalize_code(&node_text(&child, source)),
+                    visibility: std::mem::take(&mut visibility),
+                    line_number: child.start_position().row + 1,
+                    doc_comment: None,
+                });
+            }
+        }
+    }
+    fields
+}
+
+///  Extract the variants of an enum_variant_list.
+fn extract_variants(body: &tree_sitter::Node, source: &str) -> Vec<RtVariant> {
+    let mut variants = Vec::new();
+    let mut cursor = body.walk();
+    for child in body.children(&mut cursor) {
+        if child.kind() != "enum_variant" {
+            continue;
+        }
+        let Some(name) = child.child_by_field_name("name") else {
+            continue;
+        };
+        variants.push(RtVariant {
+            name: node_text(&name, source),
+            signature: contract::parse_signature(&node_text(&child, source)),
+            line_number: child.start_position().row + 1,
+            doc_comment: extract_doc_comment(&child, source),
+        });
+    }
+    variants
+}
+
+///  Collect the outer attributes preceding a node, in source order,
+///  with whitespace collapsed (e.g. `#[verifier::external_body]`).
+fn extract_attributes(node: &tree_sitter::Node, source: &str) -> Vec<String> {
+    let mut attributes = Vec::new();
+    let mut prev = node.prev_sibling();
+
+    while let Some(sibling) = prev {
+        match sibling.kind() {
+            "attribute_item" => {
+                attributes.push(contract::collapse_whitespace(&node_text(&sibling, source)));
+            }
+            "line_comment" | "block_comment" => {}
+            _ => break,
+        }
+        prev = sibling.prev_sibling();
+    }
+
+    attributes.reverse();
+    attributes
+}
+
+///  Collect the inner attributes (`#![...]`) directly inside a node, such as
+///  a source file or an inline module body, with whitespace collapsed.
+fn extract_inner_attributes(node: &tree_sitter::Node, source: &str) -> Vec<String> {
+    let mut cursor = node.walk();
+    node.children(&mut cursor)
+        .filter(|child| child.kind() == "inner_attribute_item")
+        .map(|child| contract::collapse_whitespace(&node_text(&child, source)))
+        .collect()
+}
+
+///  Collect trait names from `#[derive(...)]` attributes.
+fn extract_derives(attributes: &[String]) -> Vec<String> {
+    attributes
+        .iter()
+        .filter_map(|attr| attr.strip_prefix("#[derive(")?.strip_suffix(")]"))
+        .flat_map(|args| args.split(','))
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+///  Get the source text of a function up to its body (or the whole
+///  signature, minus the trailing `;`, for bodiless declarations).
+fn function_header(node: &tree_sitter::Node, source: &str) -> String {
+    let end = node
+        .child_by_field_name("body")
+        .map_or(node.end_byte(), |body| body.start_byte());
+    source[node.start_byte()..end]
+        .trim_end()
+        .trim_end_matches(';')
+        .to_string()
+}
+
 ///  Extract visibility from a node.
 fn extract_visibility(node: &tree_sitter::Node, source: &str) -> RtVisibility {
     let mut cursor = node.walk();
     for child in node.children(&mut cursor) {
         if child.kind() == "visibility_modifier" {
-            let text = node_text(&child, source);
-            if text.contains("crate") {
-                return RtVisibility::PublicCrate;
-            }
-            return RtVisibility::Public;
+            return parse_visibility_modifier(&node_text(&child, source));
         }
     }
     RtVisibility::Private
 }
 
-///  Extract function kind (spec/proof/exec) and open/closed status.
-fn extract_fn_kind(node: &tree_sitter::Node, source: &str) -> (RtFnKind, bool) {
+///  Map the text of a visibility_modifier node to a visibility.
+fn parse_visibility_modifier(text: &str) -> RtVisibility {
+    let compact = contract::collapse_whitespace(text);
+    let restriction = compact
+        .strip_prefix("pub")
+        .map(str::trim)
+        .and_then(|rest| rest.strip_prefix('('))
+        .and_then(|rest| rest.strip_suffix(')'))
+        .map(str::trim);
+
+    match restriction {
+        None if compact == "crate" => RtVisibility::PublicCrate,
+        None => RtVisibility::Public,
+        Some("crate") => RtVisibility::PublicCrate,
+        Some("super") => RtVisibility::PublicSuper,
+        Some("self") => RtVisibility::Private,
+        //  `pub(in crate)` and friends are the same as the short forms
+        Some(other) => match other.strip_prefix("in ").map(str::trim) {
+            Some("crate") => RtVisibility::PublicCrate,
+            Some("super") => RtVisibility::PublicSuper,
+            Some("self") => RtVisibility::Private,
+            Some(path) => RtVisibility::PublicIn(path.to_string()),
+            None => RtVisibility::Public,
+        },
+    }
+}
+
+///  Extract function kind (spec/proof/exec), open/closed status and whether
+///  the function is a `broadcast` lemma.
+fn extract_fn_kind(node: &tree_sitter::Node, source: &str) -> (RtFnKind, bool, bool) {
     let mut kind = RtFnKind::Exec;
     let mut is_open = false;
+    let mut is_broadcast = false;
 
     let mut cursor = node.walk();
     for child in node.children(&mut cursor) {
-        if child.kind() == "function_modifiers" {
-            let mut mod_cursor = child.walk();
-            for modifier in child.children(&mut mod_cursor) {
-                match node_text(&modifier, source).as_str() {
-                    "spec" => kind = RtFnKind::Spec,
-                    "proof" => kind = RtFnKind::Proof,
-                    "exec" => kind = RtFnKind::Exec,
-                    "open" => is_open = true,
-                    _ => {}
+        match child.kind() {
+            "function_modifiers" => {
+                let mut mod_cursor = child.walk();
+                for modifier in child.children(&mut mod_cursor) {
+                    match node_text(&modifier, source).as_str() {
+                        "spec" => kind = RtFnKind::Spec,
+                        "proof" => kind = RtFnKind::Proof,
+                        "exec" => kind = RtFnKind::Exec,
+                        "open" => is_open = true,
+                        "broadcast" => is_broadcast = true,
+                        _ => {}
+                    }
                 }
             }
+            //  `broadcast` may also precede the modifiers as a token of its own
+            "broadcast" => is_broadcast = true,
+            _ => {}
         }
     }
 
-    (kind, is_open)
+    (kind, is_open, is_broadcast)
 }
 
-///  Extract doc comment text from preceding siblings (///  comments).
-///  Handles both direct line_comment siblings and attribute_item nodes.
+///  Extract the inner doc (`//!`, `/*! */` and `#![doc = "..."]`) at the
+///  start of a file or of a `mod` block's contents. Plain comments, blank
+///  lines and other inner attributes may appear among them.
+pub fn extract_inner_doc(text: &str) -> Option<String> {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut fragments = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i].trim();
+        if line.starts_with("/*") {
+            //  Take the whole block comment, up to its closing line
+            let Some(close) = (i..lines.len()).find(|&j| lines[j].contains("*/")) else {
+                break;
+            };
+            fragments.extend(doc_fragment(&lines[i..=close].join("\n"), DocStyle::Inner));
+            i = close;
+        } else if line.starts_with("//") || line.starts_with("#!") {
+            fragments.extend(doc_fragment(line, DocStyle::Inner));
+        } else if !line.is_empty() {
+            break;
+        }
+        i += 1;
+    }
+    merge_doc_fragments(fragments)
+}
+
+///  A run of doc text from one source form. Consecutive `///` lines form
+///  a single fragment so their relative indentation is kept.
+struct DocFragment {
+    lines: Vec<String>,
+    is_line_comment: bool,
+}
+
+///  Extract doc text attached to an item: `///` line comments, `/** */` block
+///  comments and `#[doc = "..."]` attributes, merged in source order. Lines
+///  are kept separate so paragraphs, lists and code blocks survive.
 fn extract_doc_comment(node: &tree_sitter::Node, source: &str) -> Option<String> {
-    let mut comments = Vec::new();
+    let mut fragments = Vec::new();
     let mut prev = node.prev_sibling();
 
     while let Some(sibling) = prev {
-        if sibling.kind() == "line_comment" {
-            let text = node_text(&sibling, source);
-            if text.starts_with("///") {
-                let doc_text = text.trim_start_matches("///").trim();
-                comments.push(doc_text.to_string());
-                prev = sibling.prev_sibling();
-                continue;
-            }
-        } else if sibling.kind() == "attribute_item" {
-            //  Skip attributes like #[verifier::external] between doc comment and function
-            prev = sibling.prev_sibling();
-            continue;
+        match sibling.kind() {
+            //  Plain comments and other attributes between the docs and the
+            //  item are skipped, as in rustc
+            "line_comment" | "block_comment" | "attribute_item" => {
+                fragments.extend(doc_fragment(&node_text(&sibling, source), DocStyle::Outer));
+            }
+            _ => break,
         }
-        break;
+        prev = sibling.prev_sibling();
     }
 
     //  If no doc comments found via siblings, try looking at the source text
     //  directly above the function (handles cases where tree-sitter extras
     //  aren't proper siblings)
-    if comments.is_empty() {
+    if fragments.is_empty() {
         let start_byte = node.start_byte();
         let before = &source[..start_byte];
         let lines: Vec<&str> = before.lines().collect();
@@ -202,29 +1106,299 @@ fn extract_doc_comment(node: &tree_sitter::Node, source: &str) -> Option<String>
         while i > 0 {
             i -= 1;
             let line = lines[i].trim();
-            if line.starts_with("///") {
-                let doc_text = line.trim_start_matches("///").trim();
-                comments.push(doc_text.to_string());
-            } else if line.is_empty() || line.starts_with("#[") {
-                //  Skip blank lines and attributes
-                continue;
-            } else {
+            if line.ends_with("*/") {
+                //  Take the whole block comment, back to its opening line
+                //  A comment after code, or a plain one, ends the docs
+                let Some(open) = (0..=i).rev().find(|&j| lines[j].contains("/*")) else {
+                    break;
+                };
+                if !lines[open].trim_start().starts_with("/*") {
+                    break;
+                }
+                match doc_fragment(&lines[open..=i].join("\n"), DocStyle::Outer) {
+                    Some(fragment) => fragments.push(fragment),
+                    None => break,
+                }
+                i = open;
+            } else if line.starts_with("//") {
+                match doc_fragment(line, DocStyle::Outer) {
+                    Some(fragment) => fragments.push(fragment),
+                    None => break,
+                }
+            } else if line.starts_with("#[") {
+                fragments.extend(doc_fragment(line, DocStyle::Outer));
+            } else if !line.is_empty() {
                 break;
             }
         }
     }
 
-    if comments.is_empty() {
-        None
-    } else {
-        comments.reverse();
-        Some(comments.join(" "))
+    fragments.reverse();
+    merge_doc_fragments(fragments)
+}
+
+///  Outer docs (`///`, `/** */`, `#[doc]`) describe the following item;
+///  inner docs (`//!`, `/*! */`, `#![doc]`) describe the enclosing module.
+#[derive(Clone, Copy)]
+enum DocStyle {
+    Outer,
+    Inner,
+}
+
+impl DocStyle {
+    ///  The line comment, block comment and attribute openers of this style.
+    fn markers(self) -> (&'static str, &'static str, &'static str) {
+        match self {
+            DocStyle::Outer => ("///", "/**", "#["),
+            DocStyle::Inner => ("//!", "/*!", "#!["),
+        }
     }
 }
 
+///  Read the doc text of a comment or attribute, if it is a doc of `style`.
+fn doc_fragment(text: &str, style: DocStyle) -> Option<DocFragment> {
+    let (line_marker, block_marker, attr_marker) = style.markers();
+    let text = text.trim();
+    if let Some(line) = doc_line_text(text, line_marker) {
+        return Some(DocFragment { lines: vec![line.to_string()], is_line_comment: true });
+    }
+    let lines = doc_block_text(text, block_marker).or_else(|| doc_attribute_text(text, attr_marker))?;
+    Some(DocFragment { lines, is_line_comment: false })
+}
+
+///  The text of a `///` (or `//!`) doc comment line, without the marker or
+///  line ending. Returns None for other comments, including `////` separators.
+fn doc_line_text<'a>(line: &'a str, marker: &str) -> Option<&'a str> {
+    let text = line.trim().strip_prefix(marker)?;
+    (!text.starts_with('/')).then(|| text.trim_end())
+}
+
+///  The lines of a `/** ... */` (or `/*! ... */`) doc comment. A leading `*`
+///  on every continuation line is treated as decoration and removed. Returns
+///  None for other block comments, including `/***` and the empty `/**/`.
+fn doc_block_text(text: &str, marker: &str) -> Option<Vec<String>> {
+    let body = text.strip_prefix(marker)?.strip_suffix("*/")?;
+    if body.starts_with('*') || text == "/**/" {
+        return None;
+    }
+
+    let mut lines: Vec<&str> = body.lines().collect();
+    let decorated = lines
+        .iter()
+        .skip(1)
+        .filter(|line| !line.trim().is_empty())
+        .all(|line| line.trim_start().starts_with('*'));
+    if decorated {
+        for line in lines.iter_mut().skip(1) {
+            let trimmed = line.trim_start();
+            *line = trimmed.strip_prefix('*').unwrap_or(trimmed);
+        }
+    }
+    Some(lines.iter().map(|line| line.trim_end().to_string()).collect())
+}
+
+///  The lines of a `#[doc = "..."]` (or `#![doc = "..."]`) attribute with a
+///  string literal value. Other `doc` attributes, such as `#[doc(hidden)]`,
+///  have no text.
+fn doc_attribute_text(text: &str, marker: &str) -> Option<Vec<String>> {
+    let inner = text.strip_prefix(marker)?.strip_suffix(']')?.trim();
+    let value = inner.strip_prefix("doc")?.trim_start().strip_prefix('=')?.trim();
+    let value = parse_string_literal(value)?;
+    Some(value.lines().map(|line| line.trim_end().to_string()).collect())
+}
+
+///  Decode a Rust string literal: `"..."` with escapes or raw `r#"..."#`.
+fn parse_string_literal(literal: &str) -> Option<String> {
+    if let Some(raw) = literal.strip_prefix('r') {
+        let hashes = raw.len() - raw.trim_start_matches('#').len();
+        let fence = "#".repeat(hashes);
+        let body = raw[hashes..].strip_prefix('"')?.strip_suffix(&format!("\"{}", fence))?;
+        return Some(body.to_string());
+    }
+
+    let body = literal.strip_prefix('"')?.strip_suffix('"')?;
+    let mut out = String::new();
+    let mut chars = body.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next()? {
+            'n' => out.push('\n'),
+            't' => out.push('\t'),
+            'r' => out.push('\r'),
+            '0' => out.push('\0'),
+            //  A line continuation also skips the next line's indentation
+            '\n' => {
+                while chars.next_if(|c| c.is_whitespace()).is_some() {}
+            }
+            other => out.push(other),
+        }
+    }
+    Some(out)
+}
+
+///  Combine a module's outer doc with the inner doc of its contents, as
+///  rustc does: outer first, separated by a blank line.
+pub fn join_docs(outer: Option<String>, inner: Option<String>) -> Option<String> {
+    match (outer, inner) {
+        (Some(outer), Some(inner)) => Some(format!("{}\n\n{}", outer, inner)),
+        (outer, inner) => outer.or(inner),
+    }
+}
+
+///  Join fragments in source order. Adjacent `///` fragments are combined,
+///  each fragment's common indentation is removed, and leading or trailing
+///  blank lines are dropped. Returns None if nothing is left.
+fn merge_doc_fragments(fragments: Vec<DocFragment>) -> Option<String> {
+    let mut merged: Vec<DocFragment> = Vec::new();
+    for fragment in fragments {
+        match merged.last_mut() {
+            Some(last) if last.is_line_comment && fragment.is_line_comment => {
+                last.lines.extend(fragment.lines);
+            }
+            _ => merged.push(fragment),
+        }
+    }
+
+    let lines: Vec<String> = merged.iter().flat_map(|f| strip_common_indent(&f.lines)).collect();
+    let start = lines.iter().position(|line| !line.is_empty())?;
+    let end = lines.iter().rposition(|line| !line.is_empty())?;
+    Some(lines[start..=end].join("\n"))
+}
+
+///  The text inside a `{ ... }` block, with its common indentation stripped
+///  and surrounding blank lines removed.
+fn block_contents(block: &tree_sitter::Node, source: &str) -> String {
+    let text = node_text(block, source);
+    let text = text.trim();
+    let inner = text.strip_prefix('{').unwrap_or(text);
+    let inner = inner.strip_suffix('}').unwrap_or(inner);
+    let lines: Vec<String> = inner.lines().map(str::to_string).collect();
+    strip_common_indent(&lines).join("\n").trim_matches('\n').to_string()
+}
+
+///  Remove the indentation shared by all non-blank lines.
+fn strip_common_indent(lines: &[String]) -> Vec<String> {
+    let indent = lines
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+
+    lines
+        .iter()
+        .map(|line| line.get(indent..).unwrap_or("").trim_end().to_string())
+        .collect()
+}
+
 ///  Get the text content of a node.
 fn node_text(node: &tree_sitter::Node, source: &str) -> String {
     node.utf8_text(source.as_bytes())
         .unwrap_or("")
         .to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(source: &str) -> tree_sitter::Tree {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(&tree_sitter_verus::LANGUAGE.into()).unwrap();
+        parser.parse(source, None).unwrap()
+    }
+
+    #[test]
+    fn visibility_modifiers() {
+        assert_eq!(parse_visibility_modifier("pub"), RtVisibility::Public);
+        assert_eq!(parse_visibility_modifier("crate"), RtVisibility::PublicCrate);
+        assert_eq!(parse_visibility_modifier("pub(crate)"), RtVisibility::PublicCrate);
+        assert_eq!(parse_visibility_modifier("pub(super)"), RtVisibility::PublicSuper);
+        assert_eq!(parse_visibility_modifier("pub(self)"), RtVisibility::Private);
+        assert_eq!(parse_visibility_modifier("pub(in crate)"), RtVisibility::PublicCrate);
+        assert_eq!(parse_visibility_modifier("pub( in super )"), RtVisibility::PublicSuper);
+        assert_eq!(parse_visibility_modifier("pub(in self)"), RtVisibility::Private);
+        assert_eq!(parse_visibility_modifier("pub(in crate::a)"), RtVisibility::PublicIn("crate::a".to_string()));
+    }
+
+    #[test]
+    fn doc_lines_keep_structure_without_common_indent() {
+        let text = "//!  Summary.\n//!\n//!  - one\n//!    nested\n//!  ```\n//!  code\n//!  ```\nfn f() {}";
+        assert_eq!(
+            extract_inner_doc(text).as_deref(),
+            Some("Summary.\n\n- one\n  nested\n```\ncode\n```")
+        );
+    }
+
+    #[test]
+    fn trailing_comment_after_code_does_not_carry_docs() {
+        let items = extract_items("/// Doc of f\nfn f() {} /* end */\nfn h() {}", "lib.rs", "crate").unwrap();
+        let docs: Vec<(&str, Option<&str>)> = items.iter().map(|i| (i.name.as_str(), i.doc_comment.as_deref())).collect();
+        assert_eq!(docs, vec![("f", Some("Doc of f")), ("h", None)]);
+    }
+
+    #[test]
+    fn body_calls_are_collected_once_in_source_order() {
+        let source = "fn f(&self) { g(1); a::h::<u8>(g(2)); self.k(); x.m(); g(3); }";
+        let items = extract_items(source, "lib.rs", "crate").unwrap();
+        assert_eq!(items[0].body_calls, vec!["g", "a::h", "Self::k"]);
+    }
+
+    #[test]
+    fn broadcast_group_names_follow_the_keywords() {
+        assert_eq!(
+            broadcast_group_parts("pub(in crate::group) broadcast group group_lemmas { a, b::c }"),
+            Some(("group_lemmas", " a, b::c "))
+        );
+        assert_eq!(broadcast_group_parts("broadcast group g {}"), Some(("g", "")));
+        assert_eq!(broadcast_group_parts("broadcast groupie {}"), None);
+        assert_eq!(broadcast_group_parts("broadcast proof fn group() {}"), None);
+    }
+
+    #[test]
+    fn const_modes_are_read_from_the_header() {
+        assert_eq!(const_mode("pub const "), (RtFnKind::Exec, false));
+        assert_eq!(const_mode("pub open spec const "), (RtFnKind::Spec, true));
+        assert_eq!(const_mode("pub(in crate::spec) exec const "), (RtFnKind::Exec, false));
+        assert_eq!(const_mode("ghost static "), (RtFnKind::Spec, false));
+        assert_eq!(const_mode("tracked static "), (RtFnKind::Proof, false));
+    }
+
+    #[test]
+    fn exec_const_values_are_not_shown() {
+        let items = extract_items("/// Max.\npub const MAX: u64 = 1 << 3;\nstatic S: u8 = 0;", "lib.rs", "crate").unwrap();
+        assert_eq!(items[0].item_kind, RtItemKind::Const);
+        assert_eq!((items[0].kind, items[0].value.as_deref()), (RtFnKind::Exec, None));
+        assert_eq!(items[0].signature, "pub const MAX: u64");
+        assert_eq!(items[1].item_kind, RtItemKind::Static);
+    }
+
+    #[test]
+    fn block_contents_lose_braces_and_common_indent() {
+        let body = |source: &str| {
+            let tree = parse(source);
+            let block = tree.root_node().child(0).and_then(|f| f.child_by_field_name("body")).unwrap();
+            block_contents(&block, source)
+        };
+        assert_eq!(
+            body("fn f() {\n\n    let x = 1;\n\n    if x > 0 {\n        x\n    } else { 0 }\n}"),
+            "let x = 1;\n\nif x > 0 {\n    x\n} else { 0 }"
+        );
+        assert_eq!(body("fn g() { a && b }"), "a && b");
+    }
+
+    #[test]
+    fn only_a_self_receiver_is_a_self_parameter() {
+        let has_self = |source: &str| {
+            let tree = parse(source);
+            has_self_parameter(&tree.root_node().child(0).unwrap())
+        };
+        assert!(has_self("fn wf(&self) -> bool { true }"));
+        assert!(has_self("fn wf(self, x: u8) -> bool { true }"));
+        assert!(!has_self("fn wf(itself: u8) -> bool { true }"));
+        assert!(!has_self("fn wf(s: Self) -> bool { self_check(s) }"));
+    }
+}
@@ -0,0 +1,186 @@
+use crate::doc_item::*;
+
+///  The name of a verifier attribute, e.g. `external_body` for both
+///  `#[verifier::external_body]` and `#[verifier(external_body)]`. Inner
+///  attributes such as `#![verifier::external]` are read the same way.
+pub fn verifier_attribute_name(attribute: &str) -> Option<&str> {
+    let inner = attribute
+        .strip_prefix("#[")
+        .or_else(|| attribute.strip_prefix("#!["))?
+        .strip_suffix(']')?
+        .trim();
+    let rest = inner.strip_prefix("verifier")?.trim_start();
+    let name = match rest.strip_prefix("::") {
+        Some(path) => path,
+        None => rest.strip_prefix('(')?.strip_suffix(')')?,
+    };
+    let name = name.trim();
+    Some(name.split(['(', ' ']).next().unwrap_or(name))
+}
+
+///  The kind of trust implied by a verifier attribute such as
+///  `#[verifier::external_body]` or `#[verifier(external)]`, if any.
+pub fn attribute_trust_kind(attribute: &str) -> Option<RtTrustKind> {
+    match verifier_attribute_name(attribute)? {
+        "external_body" => Some(RtTrustKind::ExternalBody),
+        "external" => Some(RtTrustKind::External),
+        "external_fn_specification" | "external_type_specification" | "external_trait_specification" => {
+            Some(RtTrustKind::ExternalSpec)
+        }
+        "trusted" | "assume_external" => Some(RtTrustKind::Trusted),
+        _ => None,
+    }
+}
+
+///  The trust sites implied by an item's attributes, reported at its line.
+pub fn attribute_trust(item: &RtDocItem) -> Vec<RtTrustSite> {
+    attribute_sites(&item.attributes, item.line_number)
+}
+
+///  The trust sites implied by `attributes`, reported at `line_number`.
+pub fn attribute_sites(attributes: &[String], line_number: usize) -> Vec<RtTrustSite> {
+    attributes
+        .iter()
+        .filter_map(|attribute| {
+            Some(RtTrustSite {
+                kind: attribute_trust_kind(attribute)?,
+                line_number,
+                text: attribute.clone(),
+            })
+        })
+        .collect()
+}
+
+///  Add the trust of an enclosing impl, module or file to the items inside
+///  it and their members. Verus applies e.g. `#[verifier::external_body]` on
+///  an impl to every method, so each is as trusted as if it carried the
+///  attribute itself; the site is reported at the item's own line.
+pub fn inherit(items: &mut [RtDocItem], sites: &[RtTrustSite]) {
+    for item in items {
+        for site in sites {
+            if !item.trust.iter().any(|s| s.kind == site.kind && s.text == site.text) {
+                item.trust.push(RtTrustSite { line_number: item.line_number, ..site.clone() });
+            }
+        }
+        inherit(&mut item.members, sites);
+    }
+}
+
+///  The kinds of trust at `sites` with how often each occurs, in `RtTrustKind` order.
+pub fn summary(sites: &[RtTrustSite]) -> Vec<(RtTrustKind, usize)> {
+    RtTrustKind::ALL
+        .iter()
+        .map(|&kind| (kind, sites.iter().filter(|s| s.kind == kind).count()))
+        .filter(|&(_, count)| count > 0)
+        .collect()
+}
+
+///  A trust site with the item it belongs to.
+struct ReportRow<'a> {
+    item: &'a RtDocItem,
+    site: &'a RtTrustSite,
+}
+
+fn collect_rows<'a>(items: &'a [RtDocItem], rows: &mut Vec<ReportRow<'a>>) {
+    for item in items {
+        rows.extend(item.trust.iter().map(|site| ReportRow { item, site }));
+        collect_rows(&item.members, rows);
+    }
+}
+
+///  Generate the trusted-base report: every trust site in `items`, grouped by
+///  kind, with a link to its source. Pass every extracted item, not only the
+///  documented ones, since private code is part of the trusted base too.
+pub fn format_report(items: &[RtDocItem], src_prefix: &str) -> String {
+    let mut rows = Vec::new();
+    collect_rows(items, &mut rows);
+    rows.sort_by(|a, b| {
+        a.site.kind.cmp(&b.site.kind)
+            .then(a.item.file_path.cmp(&b.item.file_path))
+            .then(a.site.line_number.cmp(&b.site.line_number))
+    });
+
+    let mut md = String::new();
+    md.push_str("# Trusted Base\n\n");
+    md.push_str("*Auto-generated by verus-docgenerator*\n\n");
+    md.push_str("Everything listed here is assumed rather than verified.\n\n");
+
+    if rows.is_empty() {
+        md.push_str("No trusted sites found.\n");
+        return md;
+    }
+
+    let sites: Vec<RtTrustSite> = rows.iter().map(|r| r.site.clone()).collect();
+    md.push_str("| Kind | Count |\n|---|---|\n");
+    for (kind, count) in summary(&sites) {
+        md.push_str(&format!("| `{}` | {} |\n", kind.as_str(), count));
+    }
+    md.push('\n');
+
+    for kind in RtTrustKind::ALL {
+        let of_kind: Vec<&ReportRow> = rows.iter().filter(|r| r.site.kind == kind).collect();
+        if of_kind.is_empty() {
+            continue;
+        }
+        md.push_str(&format!("## `{}`\n\n", kind.as_str()));
+        md.push_str("| Item | Location | Code |\n|---|---|---|\n");
+        for row in of_kind {
+            let sep = if src_prefix.is_empty() || src_prefix.ends_with('/') { "" } else { "/" };
+            md.push_str(&format!(
+                "| `{path}::{name}` | [{file}:{line}]({prefix}{sep}{file}#L{line}) | `{code}` |\n",
+                path = row.item.module_path,
+                name = row.item.name,
+                file = row.item.file_path,
+                line = row.site.line_number,
+                prefix = src_prefix,
+                sep = sep,
+                code = row.site.text.replace('|', "\\|"),
+            ));
+        }
+        md.push('\n');
+    }
+
+    md
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verifier_attributes_give_their_trust_kind() {
+        assert_eq!(attribute_trust_kind("#[verifier::external_body]"), Some(RtTrustKind::ExternalBody));
+        assert_eq!(attribute_trust_kind("#[verifier(external)]"), Some(RtTrustKind::External));
+        assert_eq!(attribute_trust_kind("#![verifier::external]"), Some(RtTrustKind::External));
+        assert_eq!(
+            attribute_trust_kind("#[verifier::external_fn_specification]"),
+            Some(RtTrustKind::ExternalSpec)
+        );
+        assert_eq!(attribute_trust_kind("#[ verifier :: trusted ]"), Some(RtTrustKind::Trusted));
+        assert_eq!(attribute_trust_kind("#[verifier( external_body )]"), Some(RtTrustKind::ExternalBody));
+        assert_eq!(attribute_trust_kind("#[verifier::opaque]"), None);
+        assert_eq!(attribute_trust_kind("#[verifier::external_body_x]"), None);
+        assert_eq!(attribute_trust_kind("#[derive(Clone)]"), None);
+    }
+
+    #[test]
+    fn enclosing_trust_is_inherited_once_at_each_item() {
+        let site = RtTrustSite {
+            kind: RtTrustKind::ExternalBody,
+            line_number: 1,
+            text: "#[verifier::external_body]".to_string(),
+        };
+        let method = RtDocItem { name: "m".to_string(), line_number: 4, ..Default::default() };
+        let mut items = vec![
+            RtDocItem { name: "f".to_string(), line_number: 2, trust: vec![site.clone()], ..Default::default() },
+            RtDocItem { name: "T".to_string(), line_number: 3, members: vec![method], ..Default::default() },
+        ];
+
+        inherit(&mut items, std::slice::from_ref(&site));
+        assert_eq!(items[0].trust.len(), 1);
+        assert_eq!(items[0].trust[0].line_number, 1);
+        assert_eq!(items[1].trust[0].line_number, 3);
+        assert_eq!(items[1].members[0].trust[0].line_number, 4);
+        assert_eq!(items[1].members[0].trust[0].kind, RtTrustKind::ExternalBody);
+    }
+}
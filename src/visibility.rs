@@ -0,0 +1,255 @@
+use crate::doc_item::*;
+use crate::scope::ScopeIndex;
+
+use std::collections::{HashMap, HashSet};
+
+///  Declared visibilities of modules and types, keyed by full path, and the
+///  minimum visibility an item or enclosing module needs to be documented.
+struct VisibilityIndex {
+    min: RtVisibility,
+    modules: HashMap<String, RtVisibility>,
+    types: HashMap<String, RtVisibility>,
+    ///  Resolves `use` paths the same way as the rest of the crate.
+    scope: ScopeIndex,
+}
+
+impl VisibilityIndex {
+    fn new(items: &[RtDocItem], min: RtVisibility) -> Self {
+        let mut index = VisibilityIndex {
+            min,
+            modules: HashMap::new(),
+            types: HashMap::new(),
+            scope: ScopeIndex::new(items),
+        };
+        for item in items {
+            let path = format!("{}::{}", item.module_path, item.name);
+            match item.item_kind {
+                RtItemKind::Module => {
+                    index.modules.insert(path, item.visibility.clone());
+                }
+                RtItemKind::Struct | RtItemKind::Enum | RtItemKind::Trait => {
+                    index.types.insert(path, item.visibility.clone());
+                }
+                _ => {}
+            }
+        }
+        index
+    }
+
+    ///  Whether a declared visibility meets the minimum.
+    fn visible(&self, visibility: &RtVisibility) -> bool {
+        visibility.rank() >= self.min.rank()
+    }
+
+    ///  Whether `path` (a module, or a type's method pseudo-module) can be named
+    ///  at the minimum visibility, given that `base` can. Paths with no
+    ///  declaration (e.g. impls of external types) are as reachable as their parent.
+    fn reachable_from(&self, base: &str, path: &str) -> bool {
+        if path == base {
+            return true;
+        }
+        let Some((parent, _)) = path.rsplit_once("::") else {
+            return false;
+        };
+        if !self.reachable_from(base, parent) {
+            return false;
+        }
+        match self.modules.get(path).or_else(|| self.types.get(path)) {
+            Some(visibility) => self.visible(visibility),
+            None => true,
+        }
+    }
+
+    ///  Whether an item is reachable from the crate root at the minimum visibility.
+    fn item_reachable(&self, item: &RtDocItem) -> bool {
+        self.visible(&item.visibility) && self.reachable_from("crate", &item.module_path)
+    }
+}
+
+///  Keep the items that are reachable from the crate root at visibility `min`:
+///  items at least that visible whose enclosing modules (and, for methods,
+///  types) are too. With `min` = `Public` this is the public API. Items only
+///  reachable through a `pub use` are listed at the re-exported path, with
+///  `reexport_of` recording where they are defined. Trait impls are always
+///  kept, since they apply wherever the trait and type are visible.
+pub fn effective_visible_items(items: Vec<RtDocItem>, min: RtVisibility) -> Vec<RtDocItem> {
+    let index = VisibilityIndex::new(&items, min);
+    let (uses, items): (Vec<RtDocItem>, Vec<RtDocItem>) = items
+        .into_iter()
+        .partition(|item| item.item_kind == RtItemKind::Use);
+
+    //  Copies made by one re-export can be re-exported again, so repeat
+    //  until no `pub use` adds anything new. Each `pub use` copies a given
+    //  definition at most once, so a module re-exporting itself (`pub use
+    //  crate::a as me;` inside `a`) cannot nest copies forever.
+    let mut copies: Vec<RtDocItem> = Vec::new();
+    let mut seen: HashSet<(String, usize, String, String)> = HashSet::new();
+    loop {
+        let candidates: Vec<&RtDocItem> = items
+            .iter()
+            .chain(copies.iter())
+            .filter(|i| index.visible(&i.visibility) && !index.item_reachable(i))
+            .collect();
+
+        //  Uses in private modules still matter: they may be re-exported again.
+        //  Private imports never re-export, whatever the minimum visibility.
+        let mut round = Vec::new();
+        let reexports = uses
+            .iter()
+            .filter(|u| u.visibility != RtVisibility::Private && index.visible(&u.visibility));
+        for use_item in reexports {
+            for copy in reexport(&index, use_item, &candidates) {
+                let key = (
+                    use_item.file_path.clone(),
+                    use_item.line_number,
+                    use_item.name.clone(),
+                    copy.reexport_of.clone().unwrap_or_default(),
+                );
+                if seen.insert(key) {
+                    round.push(copy);
+                }
+            }
+        }
+
+        if round.is_empty() {
+            break;
+        }
+        copies.extend(round);
+    }
+
+    items
+        .into_iter()
+        .filter(|item| item.item_kind == RtItemKind::Impl || index.item_reachable(item))
+        .chain(copies.into_iter().filter(|c| index.reachable_from("crate", &c.module_path)))
+        .collect()
+}
+
+///  Copy the candidates named by one `pub use` item to the re-exporting module.
+fn reexport(index: &VisibilityIndex, use_item: &RtDocItem, candidates: &[&RtDocItem]) -> Vec<RtDocItem> {
+    let Some(target) = use_item
+        .reexport_of
+        .as_deref()
+        .and_then(|path| index.scope.resolve_path(&use_item.module_path, path))
+    else {
+        return Vec::new();
+    };
+    let here = &use_item.module_path;
+
+    //  `pub use m::*` brings every public item of `m` (and their methods)
+    if let Some(source_module) = target.strip_suffix("::*") {
+        return candidates
+            .iter()
+            .filter_map(|item| {
+                let rest = item.module_path.strip_prefix(source_module)?;
+                let direct = rest.is_empty();
+                let method = rest
+                    .strip_prefix("::")
+                    .is_some_and(|ty| !ty.contains("::") && index.types.contains_key(&item.module_path));
+                (direct || method).then(|| reexport_copy(item, format!("{}{}", here, rest), &item.name))
+            })
+            .collect();
+    }
+
+    //  `pub use m::name [as alias]` brings the item itself plus, for modules and
+    //  types, everything nested beneath it
+    let alias_path = format!("{}::{}", here, use_item.name);
+    let (parent, name) = target.rsplit_once("::").unwrap_or(("", target.as_str()));
+    candidates
+        .iter()
+        .filter_map(|item| {
+            if item.module_path == parent && item.name == name {
+                return Some(reexport_copy(item, here.clone(), &use_item.name));
+            }
+            let rest = item.module_path.strip_prefix(&target)?;
+            if !rest.is_empty() && !rest.starts_with("::") {
+                return None;
+            }
+            index
+                .reachable_from(&target, &item.module_path)
+                .then(|| reexport_copy(item, format!("{}{}", alias_path, rest), &item.name))
+        })
+        .collect()
+}
+
+///  Copy an item to a new location, remembering where it was defined.
+fn reexport_copy(item: &RtDocItem, module_path: String, name: &str) -> RtDocItem {
+    let mut copy = item.clone();
+    copy.reexport_of = item
+        .reexport_of
+        .clone()
+        .or_else(|| Some(format!("{}::{}", item.module_path, item.name)));
+    copy.module_path = module_path;
+    copy.name = name.to_string();
+    copy
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(name: &str, module: &str, kind: RtItemKind, visibility: RtVisibility, line: usize) -> RtDocItem {
+        RtDocItem {
+            name: name.to_string(),
+            module_path: module.to_string(),
+            item_kind: kind,
+            visibility,
+            file_path: "lib.rs".to_string(),
+            line_number: line,
+            ..Default::default()
+        }
+    }
+
+    fn reexport(name: &str, module: &str, path: &str, line: usize) -> RtDocItem {
+        RtDocItem {
+            reexport_of: Some(path.to_string()),
+            ..item(name, module, RtItemKind::Use, RtVisibility::Public, line)
+        }
+    }
+
+    #[test]
+    fn self_referential_reexport_terminates() {
+        //  mod a { pub use crate::a as me; pub fn f() {} }  pub use a::f;
+        let items = vec![
+            item("a", "crate", RtItemKind::Module, RtVisibility::Private, 1),
+            reexport("me", "crate::a", "crate::a", 2),
+            item("f", "crate::a", RtItemKind::Function, RtVisibility::Public, 3),
+            reexport("f", "crate", "a::f", 5),
+        ];
+
+        let visible = effective_visible_items(items, RtVisibility::Public);
+        let paths: Vec<String> = visible.iter().map(|i| format!("{}::{}", i.module_path, i.name)).collect();
+        assert_eq!(paths, vec!["crate::f"]);
+        assert_eq!(visible[0].reexport_of.as_deref(), Some("crate::a::f"));
+    }
+
+    #[test]
+    fn reexport_chain_is_followed() {
+        //  mod a { pub fn f() {} }  mod b { pub use crate::a::f as g; }  pub use b::g;
+        let items = vec![
+            item("a", "crate", RtItemKind::Module, RtVisibility::Private, 1),
+            item("f", "crate::a", RtItemKind::Function, RtVisibility::Public, 2),
+            item("b", "crate", RtItemKind::Module, RtVisibility::Private, 3),
+            reexport("g", "crate::b", "crate::a::f", 4),
+            reexport("g", "crate", "b::g", 5),
+        ];
+
+        let visible = effective_visible_items(items, RtVisibility::Public);
+        let paths: Vec<String> = visible.iter().map(|i| format!("{}::{}", i.module_path, i.name)).collect();
+        assert_eq!(paths, vec!["crate::g"]);
+    }
+
+    #[test]
+    fn reexport_through_private_import() {
+        //  mod a { pub fn f() {} }  use a as b;  pub use b::f;
+        let items = vec![
+            item("a", "crate", RtItemKind::Module, RtVisibility::Private, 1),
+            item("f", "crate::a", RtItemKind::Function, RtVisibility::Public, 2),
+            RtDocItem { visibility: RtVisibility::Private, ..reexport("b", "crate", "a", 3) },
+            reexport("f", "crate", "b::f", 4),
+        ];
+
+        let visible = effective_visible_items(items, RtVisibility::Public);
+        let paths: Vec<String> = visible.iter().map(|i| format!("{}::{}", i.module_path, i.name)).collect();
+        assert_eq!(paths, vec!["crate::f"]);
+    }
+}
@@ -0,0 +1,38 @@
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+///  Write a one-file crate to a fresh temporary directory and return its root.
+fn write_crate(name: &str, lib: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("verus-docgenerator-{}-{}", name, std::process::id()));
+    std::fs::create_dir_all(dir.join("src")).unwrap();
+    std::fs::write(dir.join("src/lib.rs"), lib).unwrap();
+    dir
+}
+
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_verus-docgenerator")).args(args).output().unwrap()
+}
+
+#[test]
+fn min_coverage_sets_the_exit_code() {
+    let dir = write_crate("coverage", "/// Documented.\npub fn f() {}\npub fn g() {}\n");
+    let src = dir.join("src");
+    let src = src.to_str().unwrap();
+
+    let passing = run(&["-i", src, "--min-coverage", "50"]);
+    assert_eq!(passing.status.code(), Some(0));
+    assert!(String::from_utf8_lossy(&passing.stdout).starts_with("Doc coverage: 1/2 items (50.0%)"));
+
+    let failing = run(&["-i", src, "--min-coverage", "60%"]);
+    assert_eq!(failing.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&failing.stderr).contains("Doc coverage 50.0% is below the required 60.0%"));
+
+    assert_eq!(run(&["-i", src, "--coverage"]).status.code(), Some(0));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn invalid_coverage_threshold_is_rejected() {
+    assert_eq!(run(&["--min-coverage", "101"]).status.code(), Some(1));
+    assert_eq!(run(&["--min-coverage"]).status.code(), Some(1));
+}